@@ -1,28 +1,18 @@
-use crate::wallet::{keys, WalletError};
+use crate::amount::parse_amount;
+use crate::wallet::{keys, Signer, WalletError};
 use sha2::{Digest, Sha256};
-
-const DECIMALS: u32 = 9;
-
-fn amount_to_flat_units(amount: &str) -> Result<String, WalletError> {
-    let amount = amount.trim();
-    if amount.is_empty() {
-        return Err(WalletError::Crypto("Amount cannot be empty".into()));
-    }
-    let value: f64 = amount.parse().map_err(|_| {
-        WalletError::Crypto(format!("Invalid amount: {}", amount))
-    })?;
-    if value < 0.0 || !value.is_finite() {
-        return Err(WalletError::Crypto("Amount must be a non-negative number".into()));
-    }
-    let flat = (value * 10f64.powi(DECIMALS as i32)).round();
-    if flat < 0.0 || flat > u64::MAX as f64 {
-        return Err(WalletError::Crypto("Amount too large".into()));
-    }
-    Ok((flat as u64).to_string())
-}
+use thiserror::Error;
 
 const DST_TX: &[u8] = b"AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_";
 
+const TAG_NIL: u8 = 0;
+const TAG_TRUE: u8 = 1;
+const TAG_FALSE: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_BINARY: u8 = 5;
+const TAG_LIST: u8 = 6;
+const TAG_PROPLIST: u8 = 7;
+
 fn vecpak_varint(buf: &mut Vec<u8>, v: i128) {
     if v == 0 {
         buf.push(0);
@@ -39,18 +29,18 @@ fn vecpak_varint(buf: &mut Vec<u8>, v: i128) {
 }
 
 fn vecpak_binary(buf: &mut Vec<u8>, data: &[u8]) {
-    buf.push(5);
+    buf.push(TAG_BINARY);
     vecpak_varint(buf, data.len() as i128);
     buf.extend_from_slice(data);
 }
 
 fn vecpak_int(buf: &mut Vec<u8>, n: i128) {
-    buf.push(3);
+    buf.push(TAG_INT);
     vecpak_varint(buf, n);
 }
 
 fn vecpak_list(buf: &mut Vec<u8>, items: &[Vec<u8>]) {
-    buf.push(6);
+    buf.push(TAG_LIST);
     vecpak_varint(buf, items.len() as i128);
     for item in items {
         buf.extend_from_slice(item);
@@ -59,7 +49,7 @@ fn vecpak_list(buf: &mut Vec<u8>, items: &[Vec<u8>]) {
 
 fn vecpak_proplist(buf: &mut Vec<u8>, mut pairs: Vec<(Vec<u8>, Vec<u8>)>) {
     pairs.sort_by(|a, b| a.0.cmp(&b.0));
-    buf.push(7);
+    buf.push(TAG_PROPLIST);
     vecpak_varint(buf, pairs.len() as i128);
     for (k, v) in pairs {
         buf.extend_from_slice(&k);
@@ -67,97 +57,784 @@ fn vecpak_proplist(buf: &mut Vec<u8>, mut pairs: Vec<(Vec<u8>, Vec<u8>)>) {
     }
 }
 
-fn encode_tx_action(
-    contract: &[u8],
-    function: &[u8],
-    args: &[Vec<u8>],
-    attached_symbol: Option<&[u8]>,
-    attached_amount: Option<&[u8]>,
-) -> Vec<u8> {
-    let args_ser: Vec<Vec<u8>> = args.iter().map(|a| encode_binary(a)).collect();
-    let mut list_buf = Vec::new();
-    vecpak_list(&mut list_buf, &args_ser);
+/// A contract call: `op` is always `call` for user transactions.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TxAction {
+    pub op: String,
+    #[serde(serialize_with = "serialize_name")]
+    pub contract: Vec<u8>,
+    pub function: String,
+    #[serde(serialize_with = "serialize_binary_list")]
+    pub args: Vec<Vec<u8>>,
+    pub attached_symbol: Option<String>,
+    pub attached_amount: Option<String>,
+}
 
-    let mut pairs: Vec<(Vec<u8>, Vec<u8>)> = vec![
-        (encode_binary(b"args"), list_buf),
-        (encode_binary(b"contract"), encode_binary(contract)),
-        (encode_binary(b"function"), encode_binary(function)),
-        (encode_binary(b"op"), encode_binary(b"call")),
-    ];
-    if let (Some(s), Some(a)) = (attached_symbol, attached_amount) {
-        pairs.push((encode_binary(b"attached_amount"), encode_binary(a)));
-        pairs.push((encode_binary(b"attached_symbol"), encode_binary(s)));
+impl TxAction {
+    pub fn call(contract: &[u8], function: &str, args: Vec<Vec<u8>>) -> Self {
+        TxAction {
+            op: "call".to_string(),
+            contract: contract.to_vec(),
+            function: function.to_string(),
+            args,
+            attached_symbol: None,
+            attached_amount: None,
+        }
+    }
+
+    fn to_term(&self) -> Term {
+        let mut pairs = vec![
+            (
+                bin(b"args"),
+                Term::List(self.args.iter().map(|a| Term::Binary(a.clone())).collect()),
+            ),
+            (bin(b"contract"), Term::Binary(self.contract.clone())),
+            (bin(b"function"), bin(self.function.as_bytes())),
+            (bin(b"op"), bin(self.op.as_bytes())),
+        ];
+        if let (Some(s), Some(a)) = (&self.attached_symbol, &self.attached_amount) {
+            pairs.push((bin(b"attached_amount"), bin(a.as_bytes())));
+            pairs.push((bin(b"attached_symbol"), bin(s.as_bytes())));
+        }
+        Term::PropList(pairs)
     }
-    let mut out = Vec::new();
-    vecpak_proplist(&mut out, pairs);
-    out
+
+    fn from_term(term: &Term) -> Result<Self, WalletError> {
+        check_fields(
+            term,
+            "action",
+            &[b"args", b"attached_amount", b"attached_symbol", b"contract", b"function", b"op"],
+        )?;
+        let args = field(term, b"args", "action")?
+            .as_list()
+            .ok_or_else(|| invalid("action.args must be a list"))?
+            .iter()
+            .map(|a| {
+                a.as_binary()
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| invalid("action.args must contain binaries"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let attached_symbol = optional_string_field(term, b"attached_symbol", "action")?;
+        let attached_amount = optional_string_field(term, b"attached_amount", "action")?;
+        if attached_symbol.is_some() != attached_amount.is_some() {
+            return Err(invalid("action must attach both a symbol and an amount, or neither"));
+        }
+        Ok(TxAction {
+            op: string_field(term, b"op", "action")?,
+            contract: binary_field(term, b"contract", "action")?.to_vec(),
+            function: string_field(term, b"function", "action")?,
+            args,
+            attached_symbol,
+            attached_amount,
+        })
+    }
+}
+
+/// The inner `tx` proplist; its packed bytes are what gets hashed and signed.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Transaction {
+    #[serde(serialize_with = "serialize_binary")]
+    pub signer: [u8; 48],
+    #[serde(serialize_with = "serialize_int")]
+    pub nonce: i128,
+    pub action: TxAction,
 }
 
-fn encode_binary(data: &[u8]) -> Vec<u8> {
-    let mut out = Vec::new();
-    vecpak_binary(&mut out, data);
-    out
+impl Transaction {
+    pub fn encode(&self) -> Vec<u8> {
+        self.to_term().encode()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, WalletError> {
+        Self::from_term(&decode(bytes)?)
+    }
+
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.encode()).into()
+    }
+
+    pub fn sign(self, signer: &dyn Signer) -> Result<SignedTx, WalletError> {
+        let signature = self.cosign(signer)?;
+        Ok(self.with_signature(signature))
+    }
+
+    /// One signer's share of a multisig signature. Aggregated with the others' it verifies against
+    /// `signer`, the aggregate of their public keys.
+    pub fn cosign(&self, signer: &dyn Signer) -> Result<[u8; 96], WalletError> {
+        signer.sign(&self.hash(), DST_TX)
+    }
+
+    pub fn verify_cosignature(&self, public_key: &[u8; 48], signature: &[u8; 96]) -> Result<(), WalletError> {
+        keys::verify_with_dst(public_key, &self.hash(), signature, DST_TX)
+    }
+
+    /// Wraps the transaction with a signature made elsewhere; call `verify` on the result.
+    pub fn with_signature(self, signature: [u8; 96]) -> SignedTx {
+        SignedTx {
+            hash: self.hash(),
+            signature,
+            tx: self,
+        }
+    }
+
+    fn to_term(&self) -> Term {
+        Term::PropList(vec![
+            (bin(b"action"), self.action.to_term()),
+            (bin(b"nonce"), Term::Int(self.nonce)),
+            (bin(b"signer"), Term::Binary(self.signer.to_vec())),
+        ])
+    }
+
+    fn from_term(term: &Term) -> Result<Self, WalletError> {
+        check_fields(term, "tx", &[b"action", b"nonce", b"signer"])?;
+        Ok(Transaction {
+            signer: fixed_field(term, b"signer", "tx")?,
+            nonce: field(term, b"nonce", "tx")?
+                .as_int()
+                .ok_or_else(|| invalid("tx.nonce must be an integer"))?,
+            action: TxAction::from_term(field(term, b"action", "tx")?)?,
+        })
+    }
+}
+
+/// The signed envelope (`TxU`) submitted to the node.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SignedTx {
+    #[serde(serialize_with = "serialize_binary")]
+    pub hash: [u8; 32],
+    #[serde(serialize_with = "serialize_binary")]
+    pub signature: [u8; 96],
+    pub tx: Transaction,
+}
+
+impl SignedTx {
+    pub fn encode(&self) -> Vec<u8> {
+        Term::PropList(vec![
+            (bin(b"hash"), Term::Binary(self.hash.to_vec())),
+            (bin(b"signature"), Term::Binary(self.signature.to_vec())),
+            (bin(b"tx"), self.tx.to_term()),
+        ])
+        .encode()
+    }
+
+    /// Parses a packed `TxU`. This only checks structure; call `verify` before trusting it.
+    pub fn decode(bytes: &[u8]) -> Result<Self, WalletError> {
+        let term = decode(bytes)?;
+        check_fields(&term, "txu", &[b"hash", b"signature", b"tx"])?;
+        Ok(SignedTx {
+            hash: fixed_field(&term, b"hash", "txu")?,
+            signature: fixed_field(&term, b"signature", "txu")?,
+            tx: Transaction::from_term(field(&term, b"tx", "txu")?)?,
+        })
+    }
+
+    /// The hash of a packed `TxU` without parsing it field by field: the SHA-256 of its `tx`
+    /// value as packed. Unlike `decode` this accepts fields it does not know, so it works for
+    /// transactions made by newer software.
+    pub fn hash_of(bytes: &[u8]) -> Result<[u8; 32], WalletError> {
+        let term = decode(bytes)?;
+        Ok(Sha256::digest(field(&term, b"tx", "txu")?.encode()).into())
+    }
+
+    /// Checks that `hash` is the SHA-256 of the packed `tx` and that `signature` is `signer`'s over it.
+    pub fn verify(&self) -> Result<(), WalletError> {
+        if self.tx.hash() != self.hash {
+            return Err(invalid("hash does not match tx"));
+        }
+        keys::verify_with_dst(&self.tx.signer, &self.hash, &self.signature, DST_TX)
+    }
+}
+
+fn bin(data: &[u8]) -> Term {
+    Term::Binary(data.to_vec())
+}
+
+fn invalid(msg: &str) -> WalletError {
+    WalletError::InvalidTransaction(msg.to_string())
+}
+
+/// Decoding is strict so that re-encoding a parsed transaction gives back the exact signed bytes.
+fn check_fields(term: &Term, what: &str, allowed: &[&[u8]]) -> Result<(), WalletError> {
+    let Term::PropList(pairs) = term else {
+        return Err(WalletError::InvalidTransaction(format!("{} must be a proplist", what)));
+    };
+    for (k, _) in pairs {
+        if !k.as_binary().is_some_and(|k| allowed.contains(&k)) {
+            return Err(WalletError::InvalidTransaction(format!("unexpected field in {}", what)));
+        }
+    }
+    Ok(())
+}
+
+fn field<'a>(term: &'a Term, key: &[u8], what: &str) -> Result<&'a Term, WalletError> {
+    term.get(key).ok_or_else(|| {
+        WalletError::InvalidTransaction(format!(
+            "{}.{} is missing",
+            what,
+            String::from_utf8_lossy(key)
+        ))
+    })
+}
+
+fn binary_field<'a>(term: &'a Term, key: &[u8], what: &str) -> Result<&'a [u8], WalletError> {
+    field(term, key, what)?.as_binary().ok_or_else(|| {
+        WalletError::InvalidTransaction(format!(
+            "{}.{} must be a binary",
+            what,
+            String::from_utf8_lossy(key)
+        ))
+    })
+}
+
+fn fixed_field<const N: usize>(term: &Term, key: &[u8], what: &str) -> Result<[u8; N], WalletError> {
+    binary_field(term, key, what)?.try_into().map_err(|_| {
+        WalletError::InvalidTransaction(format!(
+            "{}.{} must be {} bytes",
+            what,
+            String::from_utf8_lossy(key),
+            N
+        ))
+    })
+}
+
+fn string_field(term: &Term, key: &[u8], what: &str) -> Result<String, WalletError> {
+    String::from_utf8(binary_field(term, key, what)?.to_vec()).map_err(|_| {
+        WalletError::InvalidTransaction(format!(
+            "{}.{} must be UTF-8",
+            what,
+            String::from_utf8_lossy(key)
+        ))
+    })
+}
+
+fn optional_string_field(term: &Term, key: &[u8], what: &str) -> Result<Option<String>, WalletError> {
+    match term.get(key) {
+        Some(_) => string_field(term, key, what).map(Some),
+        None => Ok(None),
+    }
 }
 
 pub fn build_coin_transfer(
-    private_key_base58: &str,
+    signer: &dyn Signer,
+    nonce: i128,
     recipient_base58: &str,
     amount: &str,
     symbol: &str,
 ) -> Result<Vec<u8>, WalletError> {
-    let sk_bytes = keys::decode_private_key_base58(private_key_base58)?;
-    let sender_pk = keys::public_key_bytes_from_private_base58(private_key_base58)?;
-    let recipient_bytes = bs58::decode(recipient_base58.trim())
-        .into_vec()
-        .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
-    if recipient_bytes.len() != 48 {
-        return Err(WalletError::InvalidPrivateKey(format!(
-            "Recipient must be 48 bytes (Base58), got {}",
-            recipient_bytes.len()
-        )));
-    }
-
-    let amount_flat = amount_to_flat_units(amount)?;
+    let tx = coin_transfer_transaction(signer.public_key(), nonce, recipient_base58, amount, symbol)?;
+    Ok(tx.sign(signer)?.encode())
+}
+
+/// The unsigned `Coin.transfer` that `build_coin_transfer` would sign, for previewing.
+pub fn coin_transfer_transaction(
+    signer: [u8; 48],
+    nonce: i128,
+    recipient_base58: &str,
+    amount: &str,
+    symbol: &str,
+) -> Result<Transaction, WalletError> {
+    let recipient_bytes = keys::check_public_key_base58(recipient_base58).map_err(WalletError::InvalidRecipient)?;
+
+    let amount_flat = parse_amount(amount)?.to_string();
     let symbol = if symbol.is_empty() { "AMA" } else { symbol };
     let args: Vec<Vec<u8>> = vec![
-        recipient_bytes,
+        recipient_bytes.to_vec(),
         amount_flat.as_bytes().to_vec(),
         symbol.as_bytes().to_vec(),
     ];
 
-    let nonce = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_nanos() as i128)
-        .unwrap_or(0);
-
-    let action_ser = encode_tx_action(b"Coin", b"transfer", &args, None, None);
-    let signer_ser = encode_binary(&sender_pk);
-
-    let mut tx_pairs: Vec<(Vec<u8>, Vec<u8>)> = vec![
-        (encode_binary(b"action"), action_ser),
-        (encode_binary(b"nonce"), {
-            let mut b = Vec::new();
-            vecpak_int(&mut b, nonce);
-            b
-        }),
-        (encode_binary(b"signer"), signer_ser),
-    ];
-    tx_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(Transaction {
+        signer,
+        nonce,
+        action: TxAction::call(b"Coin", "transfer", args),
+    })
+}
 
-    let mut tx_ser = Vec::new();
-    vecpak_proplist(&mut tx_ser, tx_pairs);
+/// A contract call argument as the frontend describes it; every variant ends up as a binary.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ContractArg {
+    /// UTF-8 text, passed through as-is.
+    String(String),
+    /// Raw bytes written as Base58, e.g. an address.
+    Base58(String),
+    /// An integer, sent as its decimal string the way the chain expects numbers.
+    Int(String),
+    /// A coin amount in whole units (`"1.5"`), sent as flat units.
+    Amount(String),
+}
 
-    let hash: [u8; 32] = Sha256::digest(&tx_ser).into();
-    let signature = keys::sign_hash_with_dst(&sk_bytes, &hash, DST_TX)?;
+impl ContractArg {
+    fn to_bytes(&self) -> Result<Vec<u8>, WalletError> {
+        match self {
+            ContractArg::String(s) => Ok(s.as_bytes().to_vec()),
+            ContractArg::Base58(s) => bs58::decode(s.trim())
+                .into_vec()
+                .map_err(|e| WalletError::InvalidTransaction(format!("Invalid Base58 argument: {}", e))),
+            ContractArg::Int(s) => s
+                .trim()
+                .parse::<i128>()
+                .map(|n| n.to_string().into_bytes())
+                .map_err(|_| WalletError::InvalidTransaction(format!("Invalid integer argument: {}", s))),
+            ContractArg::Amount(s) => Ok(parse_amount(s)?.to_string().into_bytes()),
+        }
+    }
+}
 
-    let mut txu_pairs: Vec<(Vec<u8>, Vec<u8>)> = vec![
-        (encode_binary(b"hash"), encode_binary(&hash)),
-        (encode_binary(b"signature"), encode_binary(&signature)),
-        (encode_binary(b"tx"), tx_ser),
-    ];
-    txu_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+/// Coins sent along with a contract call.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Attachment {
+    pub symbol: String,
+    /// Whole units, like the `amount` of a transfer.
+    pub amount: String,
+}
 
-    let mut out = Vec::new();
-    vecpak_proplist(&mut out, txu_pairs);
-    Ok(out)
+pub fn build_contract_call(
+    signer: &dyn Signer,
+    nonce: i128,
+    contract: &str,
+    function: &str,
+    args: &[ContractArg],
+    attachment: Option<&Attachment>,
+) -> Result<Vec<u8>, WalletError> {
+    let tx = contract_call_transaction(signer.public_key(), nonce, contract, function, args, attachment)?;
+    Ok(tx.sign(signer)?.encode())
+}
+
+/// `contract` is a built-in contract name such as `Coin`, or the Base58 address of a deployed contract.
+pub fn contract_call_transaction(
+    signer: [u8; 48],
+    nonce: i128,
+    contract: &str,
+    function: &str,
+    args: &[ContractArg],
+    attachment: Option<&Attachment>,
+) -> Result<Transaction, WalletError> {
+    let contract = contract.trim();
+    let function = function.trim();
+    if contract.is_empty() || function.is_empty() {
+        return Err(WalletError::InvalidTransaction(
+            "Contract and function are required".into(),
+        ));
+    }
+    let contract_bytes = match bs58::decode(contract).into_vec() {
+        Ok(address) if address.len() == 48 => address,
+        _ => contract.as_bytes().to_vec(),
+    };
+    let args = args
+        .iter()
+        .map(ContractArg::to_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut action = TxAction::call(&contract_bytes, function, args);
+    if let Some(attachment) = attachment {
+        let symbol = attachment.symbol.trim();
+        if symbol.is_empty() {
+            return Err(WalletError::InvalidTransaction(
+                "Attachment symbol is required".into(),
+            ));
+        }
+        action.attached_symbol = Some(symbol.to_string());
+        action.attached_amount = Some(parse_amount(&attachment.amount)?.to_string());
+    }
+
+    Ok(Transaction {
+        signer,
+        nonce,
+        action,
+    })
+}
+
+/// Nesting limit for the decoder; transactions never go deeper than a handful of levels.
+const MAX_DEPTH: usize = 32;
+
+/// A decoded vecpak value.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Term {
+    Nil,
+    Bool(bool),
+    #[serde(serialize_with = "serialize_int")]
+    Int(i128),
+    #[serde(serialize_with = "serialize_binary")]
+    Binary(Vec<u8>),
+    List(Vec<Term>),
+    PropList(Vec<(Term, Term)>),
+}
+
+fn serialize_int<S: serde::Serializer>(n: &i128, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&n.to_string())
+}
+
+/// Binaries go to the frontend as Base58, the same way keys and transactions do.
+fn serialize_binary<S: serde::Serializer, B: AsRef<[u8]>>(b: &B, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&bs58::encode(b).into_string())
+}
+
+fn serialize_binary_list<S: serde::Serializer>(items: &[Vec<u8>], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(items.iter().map(|b| bs58::encode(b).into_string()))
+}
+
+/// Built-in contracts are named (`Coin`), deployed ones are addressed by public key.
+fn serialize_name<S: serde::Serializer>(b: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    match std::str::from_utf8(b) {
+        Ok(name) if !name.is_empty() && name.bytes().all(|c| c.is_ascii_graphic()) => s.serialize_str(name),
+        _ => s.serialize_str(&bs58::encode(b).into_string()),
+    }
+}
+
+impl Term {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_into(&mut buf);
+        buf
+    }
+
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        match self {
+            Term::Nil => buf.push(TAG_NIL),
+            Term::Bool(true) => buf.push(TAG_TRUE),
+            Term::Bool(false) => buf.push(TAG_FALSE),
+            Term::Int(n) => vecpak_int(buf, *n),
+            Term::Binary(b) => vecpak_binary(buf, b),
+            Term::List(items) => {
+                let items: Vec<Vec<u8>> = items.iter().map(Term::encode).collect();
+                vecpak_list(buf, &items);
+            }
+            Term::PropList(pairs) => {
+                let pairs = pairs.iter().map(|(k, v)| (k.encode(), v.encode())).collect();
+                vecpak_proplist(buf, pairs);
+            }
+        }
+    }
+
+    pub fn as_binary(&self) -> Option<&[u8]> {
+        match self {
+            Term::Binary(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Term::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Term]> {
+        match self {
+            Term::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Looks up a binary key in a proplist.
+    pub fn get(&self, key: &[u8]) -> Option<&Term> {
+        match self {
+            Term::PropList(pairs) => pairs
+                .iter()
+                .find(|(k, _)| k.as_binary() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum VecpakError {
+    #[error("unexpected end of input at byte {0}")]
+    UnexpectedEof(usize),
+
+    #[error("unknown tag {tag} at byte {offset}")]
+    UnknownTag { tag: u8, offset: usize },
+
+    #[error("non-canonical encoding at byte {0}")]
+    NonCanonical(usize),
+
+    #[error("invalid length at byte {0}")]
+    InvalidLength(usize),
+
+    #[error("integer out of range at byte {0}")]
+    IntOutOfRange(usize),
+
+    #[error("proplist keys out of order or duplicated at byte {0}")]
+    UnsortedKeys(usize),
+
+    #[error("nesting deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+
+    #[error("{0} trailing bytes after value")]
+    TrailingBytes(usize),
+}
+
+/// Decodes exactly one canonical vecpak value; anything left over is an error.
+pub fn decode(bytes: &[u8]) -> Result<Term, VecpakError> {
+    let mut reader = Reader { buf: bytes, pos: 0 };
+    let term = reader.term(0)?;
+    if reader.pos != bytes.len() {
+        return Err(VecpakError::TrailingBytes(bytes.len() - reader.pos));
+    }
+    Ok(term)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, VecpakError> {
+        let b = *self
+            .buf
+            .get(self.pos)
+            .ok_or(VecpakError::UnexpectedEof(self.pos))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], VecpakError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.buf.len())
+            .ok_or(VecpakError::UnexpectedEof(self.buf.len()))?;
+        let out = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Inverse of `vecpak_varint`: rejects negative zero, over-long lengths and leading zero bytes.
+    fn varint(&mut self) -> Result<i128, VecpakError> {
+        let start = self.pos;
+        let head = self.byte()?;
+        let negative = head & 0x80 != 0;
+        let len = (head & 0x7f) as usize;
+        if len == 0 {
+            return if negative {
+                Err(VecpakError::NonCanonical(start))
+            } else {
+                Ok(0)
+            };
+        }
+        if len > 16 {
+            return Err(VecpakError::IntOutOfRange(start));
+        }
+        let bytes = self.take(len)?;
+        if bytes[0] == 0 {
+            return Err(VecpakError::NonCanonical(start));
+        }
+        let mut be = [0u8; 16];
+        be[16 - len..].copy_from_slice(bytes);
+        let mag = u128::from_be_bytes(be);
+        if negative {
+            0i128
+                .checked_sub_unsigned(mag)
+                .ok_or(VecpakError::IntOutOfRange(start))
+        } else {
+            i128::try_from(mag).map_err(|_| VecpakError::IntOutOfRange(start))
+        }
+    }
+
+    /// A length or element count; bounded by what is left in the buffer so garbage cannot force huge allocations.
+    fn length(&mut self) -> Result<usize, VecpakError> {
+        let start = self.pos;
+        let n = self.varint()?;
+        usize::try_from(n)
+            .ok()
+            .filter(|&n| n <= self.remaining())
+            .ok_or(VecpakError::InvalidLength(start))
+    }
+
+    fn term(&mut self, depth: usize) -> Result<Term, VecpakError> {
+        if depth > MAX_DEPTH {
+            return Err(VecpakError::TooDeep);
+        }
+        let offset = self.pos;
+        match self.byte()? {
+            TAG_NIL => Ok(Term::Nil),
+            TAG_TRUE => Ok(Term::Bool(true)),
+            TAG_FALSE => Ok(Term::Bool(false)),
+            TAG_INT => Ok(Term::Int(self.varint()?)),
+            TAG_BINARY => {
+                let len = self.length()?;
+                Ok(Term::Binary(self.take(len)?.to_vec()))
+            }
+            TAG_LIST => {
+                let count = self.length()?;
+                let mut items = Vec::with_capacity(count);
+                for _ in 0..count {
+                    items.push(self.term(depth + 1)?);
+                }
+                Ok(Term::List(items))
+            }
+            TAG_PROPLIST => {
+                let count = self.length()?;
+                let mut pairs = Vec::with_capacity(count);
+                let mut prev_key: Option<&[u8]> = None;
+                for _ in 0..count {
+                    let key_start = self.pos;
+                    let key = self.term(depth + 1)?;
+                    let key_bytes = &self.buf[key_start..self.pos];
+                    if prev_key.is_some_and(|prev| prev >= key_bytes) {
+                        return Err(VecpakError::UnsortedKeys(key_start));
+                    }
+                    prev_key = Some(key_bytes);
+                    let value = self.term(depth + 1)?;
+                    pairs.push((key, value));
+                }
+                Ok(Term::PropList(pairs))
+            }
+            tag => Err(VecpakError::UnknownTag { tag, offset }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(term: Term) {
+        let bytes = term.encode();
+        assert_eq!(decode(&bytes), Ok(term));
+    }
+
+    fn nested_lists(depth: usize) -> Vec<u8> {
+        let mut bytes = vec![TAG_NIL];
+        for _ in 0..depth {
+            let mut outer = vec![TAG_LIST, 1, 1];
+            outer.extend_from_slice(&bytes);
+            bytes = outer;
+        }
+        bytes
+    }
+
+    /// A transfer signed with a fixed key.
+    fn signed_transfer() -> SignedTx {
+        let sk = bs58::encode([7u8; 64]).into_string();
+        let signer = keys::decode_public_key_base58(&keys::public_key_from_private_base58(&sk).unwrap()).unwrap();
+        let action = TxAction::call(b"Coin", "transfer", vec![vec![3; 48], b"1000".to_vec(), b"AMA".to_vec()]);
+        Transaction { signer, nonce: 42, action }
+            .sign(&keys::decode_private_key_base58(&sk).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn integers_round_trip() {
+        for n in [0, 1, -1, 127, 128, -128, 255, 256, i64::MAX as i128, i64::MIN as i128, i128::MAX, i128::MIN] {
+            round_trip(Term::Int(n));
+        }
+    }
+
+    #[test]
+    fn terms_round_trip() {
+        round_trip(Term::Nil);
+        round_trip(Term::Bool(true));
+        round_trip(Term::Bool(false));
+        round_trip(Term::Binary(Vec::new()));
+        round_trip(Term::Binary(vec![0xab; 300]));
+        round_trip(Term::List(vec![Term::Int(1), Term::Binary(b"x".to_vec()), Term::Nil]));
+        round_trip(Term::PropList(vec![
+            (Term::Binary(b"a".to_vec()), Term::Int(-5)),
+            (Term::Binary(b"b".to_vec()), Term::List(vec![Term::Bool(true)])),
+        ]));
+    }
+
+    #[test]
+    fn transactions_round_trip() {
+        let signed = signed_transfer();
+        assert_eq!(Transaction::decode(&signed.tx.encode()).unwrap(), signed.tx);
+        let bytes = signed.encode();
+        assert_eq!(SignedTx::decode(&bytes).unwrap(), signed);
+        assert_eq!(SignedTx::hash_of(&bytes).unwrap(), signed.hash);
+    }
+
+    #[test]
+    fn hash_of_accepts_unknown_fields() {
+        let tx = Transaction {
+            signer: [1; 48],
+            nonce: 42,
+            action: TxAction::call(b"Coin", "transfer", Vec::new()),
+        };
+        let bytes = Term::PropList(vec![
+            (bin(b"hash"), Term::Binary(tx.hash().to_vec())),
+            (bin(b"signature"), Term::Binary(vec![0; 96])),
+            (bin(b"tx"), tx.to_term()),
+            (bin(b"zzz"), Term::Nil),
+        ])
+        .encode();
+        assert!(SignedTx::decode(&bytes).is_err());
+        assert_eq!(SignedTx::hash_of(&bytes).unwrap(), tx.hash());
+    }
+
+    #[test]
+    fn verifies_a_signed_transaction() {
+        assert!(signed_transfer().verify().is_ok());
+    }
+
+    #[test]
+    fn rejects_a_flipped_byte_in_the_tx() {
+        let mut tampered = signed_transfer();
+        tampered.tx.action.args[1][0] ^= 1;
+        assert!(matches!(tampered.verify(), Err(WalletError::InvalidTransaction(_))));
+        tampered.hash = tampered.tx.hash();
+        assert!(matches!(tampered.verify(), Err(WalletError::InvalidSignature)));
+    }
+
+    #[test]
+    fn rejects_a_flipped_byte_in_the_signature() {
+        let mut tampered = signed_transfer();
+        tampered.signature[50] ^= 1;
+        assert!(matches!(tampered.verify(), Err(WalletError::InvalidSignature)));
+    }
+
+    #[test]
+    fn rejects_negative_zero() {
+        assert_eq!(decode(&[TAG_INT, 0x80]), Err(VecpakError::NonCanonical(1)));
+    }
+
+    #[test]
+    fn rejects_leading_zero_bytes() {
+        assert_eq!(decode(&[TAG_INT, 2, 0, 1]), Err(VecpakError::NonCanonical(1)));
+        assert_eq!(decode(&[TAG_BINARY, 2, 0, 1, b'x']), Err(VecpakError::NonCanonical(1)));
+    }
+
+    #[test]
+    fn rejects_out_of_range_integers() {
+        assert_eq!(decode(&[TAG_INT, 17]), Err(VecpakError::IntOutOfRange(1)));
+        let mut too_big = vec![TAG_INT, 16, 0x80];
+        too_big.extend_from_slice(&[0; 15]);
+        assert_eq!(decode(&too_big), Err(VecpakError::IntOutOfRange(1)));
+    }
+
+    #[test]
+    fn rejects_unsorted_or_duplicate_keys() {
+        let pair = |k: &[u8]| [Term::Binary(k.to_vec()).encode(), Term::Nil.encode()].concat();
+        let unsorted = [vec![TAG_PROPLIST, 1, 2], pair(b"b"), pair(b"a")].concat();
+        assert_eq!(decode(&unsorted), Err(VecpakError::UnsortedKeys(8)));
+        let duplicate = [vec![TAG_PROPLIST, 1, 2], pair(b"a"), pair(b"a")].concat();
+        assert_eq!(decode(&duplicate), Err(VecpakError::UnsortedKeys(8)));
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        assert_eq!(decode(&[TAG_NIL, TAG_NIL, TAG_NIL]), Err(VecpakError::TrailingBytes(2)));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert_eq!(decode(&[TAG_BINARY, 1, 3, b'a']), Err(VecpakError::InvalidLength(1)));
+        assert_eq!(decode(&[TAG_INT, 2, 1]), Err(VecpakError::UnexpectedEof(3)));
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        assert!(decode(&nested_lists(MAX_DEPTH)).is_ok());
+        assert_eq!(decode(&nested_lists(MAX_DEPTH + 1)), Err(VecpakError::TooDeep));
+    }
 }
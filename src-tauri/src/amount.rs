@@ -0,0 +1,117 @@
+use crate::wallet::WalletError;
+
+/// AMA and every other on-chain symbol use 9 decimal places.
+pub const DECIMALS: usize = 9;
+
+const ONE: u128 = 10u128.pow(DECIMALS as u32);
+
+/// Parses a plain decimal string (`"12"`, `"0.5"`, `".25"`) into flat units, exactly.
+///
+/// Signs, exponents, separators and more than `DECIMALS` significant fractional digits are rejected
+/// rather than rounded.
+pub fn parse_amount(amount: &str) -> Result<u128, WalletError> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Err(WalletError::InvalidAmount("cannot be empty".into()));
+    }
+    let (int_part, frac_part) = match amount.split_once('.') {
+        Some((i, f)) => (i, f),
+        None => (amount, ""),
+    };
+    let all_digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+    if !all_digits(int_part)
+        || !all_digits(frac_part)
+        || (int_part.is_empty() && frac_part.is_empty())
+        || (amount.contains('.') && frac_part.is_empty())
+    {
+        return Err(WalletError::InvalidAmount(format!("'{}' is not a plain decimal number", amount)));
+    }
+
+    let frac_part = frac_part.trim_end_matches('0');
+    if frac_part.len() > DECIMALS {
+        return Err(WalletError::InvalidAmount(format!("more than {} decimal places", DECIMALS)));
+    }
+
+    let too_large = || WalletError::InvalidAmount("too large".into());
+    let int_value = if int_part.is_empty() {
+        0
+    } else {
+        int_part.parse::<u128>().map_err(|_| too_large())?
+    };
+    let frac_value = if frac_part.is_empty() {
+        0
+    } else {
+        frac_part.parse::<u128>().map_err(|_| too_large())? * 10u128.pow((DECIMALS - frac_part.len()) as u32)
+    };
+    int_value
+        .checked_mul(ONE)
+        .and_then(|v| v.checked_add(frac_value))
+        .ok_or_else(too_large)
+}
+
+/// Formats flat units as a decimal string without trailing zeros: `1500000000` -> `"1.5"`.
+pub fn format_amount(flat: u128) -> String {
+    let int_part = flat / ONE;
+    let frac_part = flat % ONE;
+    if frac_part == 0 {
+        return int_part.to_string();
+    }
+    let frac = format!("{:0width$}", frac_part, width = DECIMALS);
+    format!("{}.{}", int_part, frac.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: &str = "340282366920938463463374607431.768211455";
+
+    #[test]
+    fn parses_plain_decimals() {
+        assert_eq!(parse_amount("12").unwrap(), 12 * ONE);
+        assert_eq!(parse_amount("0.5").unwrap(), ONE / 2);
+        assert_eq!(parse_amount(".25").unwrap(), ONE / 4);
+        assert_eq!(parse_amount("007").unwrap(), 7 * ONE);
+        assert_eq!(parse_amount(" 1.5 ").unwrap(), 1_500_000_000);
+        assert_eq!(parse_amount("0").unwrap(), 0);
+        assert_eq!(parse_amount("0.000000001").unwrap(), 1);
+    }
+
+    #[test]
+    fn trailing_zeros_do_not_count_as_decimal_places() {
+        assert_eq!(parse_amount("1.1000000000000").unwrap(), 1_100_000_000);
+        assert!(parse_amount("0.0000000001").is_err());
+        assert!(parse_amount("0.0000000015").is_err());
+    }
+
+    #[test]
+    fn rejects_anything_but_plain_decimals() {
+        for input in ["", "  ", ".", "1.", "-1", "+1", "1e9", "1,000", "1_000", "1.2.3", "0x10", "１"] {
+            assert!(parse_amount(input).is_err(), "{:?} was accepted", input);
+        }
+    }
+
+    #[test]
+    fn parses_up_to_u128_max() {
+        assert_eq!(parse_amount(MAX).unwrap(), u128::MAX);
+        assert!(parse_amount("340282366920938463463374607431.768211456").is_err());
+        assert!(parse_amount("340282366920938463463374607432").is_err());
+        assert!(parse_amount("999999999999999999999999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn formats_without_trailing_zeros() {
+        assert_eq!(format_amount(0), "0");
+        assert_eq!(format_amount(1), "0.000000001");
+        assert_eq!(format_amount(1_500_000_000), "1.5");
+        assert_eq!(format_amount(12 * ONE), "12");
+        assert_eq!(format_amount(u128::MAX), MAX);
+    }
+
+    #[test]
+    fn format_then_parse_round_trips() {
+        for flat in [0, 1, 10, ONE - 1, ONE, ONE + 1, 123_456_789_012_345, u128::MAX - 1, u128::MAX] {
+            assert_eq!(parse_amount(&format_amount(flat)).unwrap(), flat);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::network::NetworkError;
+use crate::wallet::WalletError;
+
+/// What every command rejects with: a stable `code` to branch on (and to look up a localized
+/// message), the English `message`, and `details` for the codes that carry structured data.
+#[derive(Debug, serde::Serialize)]
+pub struct CommandError {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl From<WalletError> for CommandError {
+    fn from(e: WalletError) -> Self {
+        match e {
+            WalletError::Network(inner) => inner.into(),
+            e => CommandError {
+                code: e.code(),
+                message: e.to_string(),
+                details: e.details(),
+            },
+        }
+    }
+}
+
+impl From<NetworkError> for CommandError {
+    fn from(e: NetworkError) -> Self {
+        CommandError {
+            code: e.code(),
+            message: e.to_string(),
+            details: e.details(),
+        }
+    }
+}
@@ -1,7 +1,13 @@
 mod amadeus_tx;
+mod amount;
+mod error;
 mod network;
 mod wallet;
 
+use error::CommandError;
+use std::path::Path;
+use tauri::Manager;
+
 #[derive(serde::Deserialize)]
 struct ChangePasswordArgs {
     #[serde(rename = "currentPassword")]
@@ -10,130 +16,628 @@ struct ChangePasswordArgs {
     new_password: String,
 }
 
+/// Returns the new private key so the user can back it up; this is the only time it leaves Rust.
 #[tauri::command]
-fn wallet_create(app: tauri::AppHandle, password: String) -> Result<String, String> {
-    wallet::create_wallet(&app, &password).map_err(|e| e.to_string())
+fn wallet_create(app: tauri::AppHandle, password: String, label: Option<String>) -> Result<String, CommandError> {
+    wallet::create_wallet(&app, &password, label.as_deref())
+        .map(|sk| sk.expose().to_string())
+        .map_err(CommandError::from)
 }
 
 #[tauri::command]
 fn wallet_import(
     app: tauri::AppHandle,
-    private_key_base58: String,
+    private_key_base58: wallet::SecretString,
+    password: String,
+    label: Option<String>,
+    force: Option<bool>,
+) -> Result<Option<String>, CommandError> {
+    wallet::import_wallet(
+        &app,
+        private_key_base58.expose(),
+        &password,
+        label.as_deref(),
+        force.unwrap_or(false),
+    )
+    .map(|backup| backup.map(|p| p.display().to_string()))
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_export_mnemonic(
+    app: tauri::AppHandle,
+    password: String,
+    passphrase: Option<wallet::SecretString>,
+) -> Result<String, CommandError> {
+    let passphrase = passphrase.as_ref().map_or("", |p| p.expose());
+    wallet::export_mnemonic(&app, &password, passphrase)
+        .map(|words| words.expose().to_string())
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_import_mnemonic(
+    app: tauri::AppHandle,
+    mnemonic: wallet::SecretString,
+    passphrase: Option<wallet::SecretString>,
+    password: String,
+    label: Option<String>,
+    force: Option<bool>,
+) -> Result<Option<String>, CommandError> {
+    wallet::import_mnemonic(
+        &app,
+        mnemonic.expose(),
+        passphrase.as_ref().map_or("", |p| p.expose()),
+        &password,
+        label.as_deref(),
+        force.unwrap_or(false),
+    )
+    .map(|backup| backup.map(|p| p.display().to_string()))
+    .map_err(CommandError::from)
+}
+
+/// Returns the shares to hand out; each one alone reveals nothing about the key.
+#[tauri::command]
+fn wallet_split_key(
+    app: tauri::AppHandle,
+    password: String,
+    threshold: u8,
+    shares: u8,
+    encoding: Option<wallet::ShareEncoding>,
+) -> Result<Vec<String>, CommandError> {
+    wallet::split_key(&app, &password, threshold, shares, encoding.unwrap_or_default())
+        .map(|shares| shares.iter().map(|s| s.expose().to_string()).collect())
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_recover_key(
+    app: tauri::AppHandle,
+    shares: Vec<wallet::SecretString>,
+    password: String,
+    label: Option<String>,
+    force: Option<bool>,
+) -> Result<Option<String>, CommandError> {
+    let shares: Vec<&str> = shares.iter().map(|s| s.expose()).collect();
+    wallet::recover_from_shares(&app, &shares, &password, label.as_deref(), force.unwrap_or(false))
+        .map(|backup| backup.map(|p| p.display().to_string()))
+        .map_err(CommandError::from)
+}
+
+/// `backup_password` protects the bundle; each account inside keeps its own password as well.
+#[tauri::command]
+fn wallet_export_backup(
+    app: tauri::AppHandle,
+    backup_password: String,
+    path: String,
+    settings: Option<wallet::BackupSettings>,
+) -> Result<wallet::BackupMetadata, CommandError> {
+    wallet::export_backup(&app, &backup_password, settings.unwrap_or_default(), Path::new(&path))
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_import_backup(
+    app: tauri::AppHandle,
+    backup_password: String,
+    path: String,
+) -> Result<wallet::RestoredBackup, CommandError> {
+    wallet::import_backup(&app, &backup_password, Path::new(&path)).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_derive_account(
+    app: tauri::AppHandle,
+    password: String,
+    index: Option<u32>,
+    label: Option<String>,
+) -> Result<wallet::Account, CommandError> {
+    wallet::derive_account(&app, &password, index, label.as_deref()).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn wallet_scan_accounts(
+    app: tauri::AppHandle,
+    password: String,
+    api_url: String,
+    gap_limit: Option<u32>,
+) -> Result<Vec<wallet::Account>, CommandError> {
+    wallet::scan_derived_accounts(&app, &password, &api_url, gap_limit)
+        .await
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_list_accounts(app: tauri::AppHandle) -> Result<wallet::AccountList, CommandError> {
+    wallet::list_accounts(&app).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_rename_account(app: tauri::AppHandle, id: String, label: String) -> Result<(), CommandError> {
+    wallet::rename_account(&app, &id, &label).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_select_account(app: tauri::AppHandle, id: String) -> Result<(), CommandError> {
+    wallet::select_account(&app, &id).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_delete_account(
+    app: tauri::AppHandle,
+    id: String,
     password: String,
-) -> Result<(), String> {
-    wallet::import_wallet(&app, &private_key_base58, &password).map_err(|e| e.to_string())
+) -> Result<Option<String>, CommandError> {
+    wallet::delete_account(&app, &id, &password)
+        .map(|backup| backup.map(|p| p.display().to_string()))
+        .map_err(CommandError::from)
 }
 
 #[tauri::command]
-fn wallet_unlock(app: tauri::AppHandle, password: String) -> Result<String, String> {
-    wallet::unlock_wallet(&app, &password).map_err(|e| e.to_string())
+fn wallet_unlock(app: tauri::AppHandle, password: String) -> Result<String, CommandError> {
+    wallet::unlock_wallet(&app, &password).map_err(CommandError::from)
 }
 
 #[tauri::command]
-fn wallet_has(app: tauri::AppHandle) -> Result<bool, String> {
-    wallet::has_wallet(&app).map_err(|e| e.to_string())
+fn wallet_lock(app: tauri::AppHandle) {
+    wallet::lock_wallet(&app)
 }
 
 #[tauri::command]
-fn wallet_public_key_from_private(private_key_base58: String) -> Result<String, String> {
-    wallet::public_key_from_private_base58(&private_key_base58).map_err(|e| e.to_string())
+fn wallet_session_status(app: tauri::AppHandle) -> wallet::SessionStatus {
+    wallet::session_status(&app)
 }
 
 #[tauri::command]
-fn wallet_file_path(app: tauri::AppHandle) -> Result<String, String> {
+fn wallet_set_auto_lock(app: tauri::AppHandle, timeout_secs: u64) -> Result<(), CommandError> {
+    wallet::set_auto_lock_timeout(&app, timeout_secs).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_has(app: tauri::AppHandle) -> Result<bool, CommandError> {
+    wallet::has_wallet(&app).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_public_key_from_private(private_key_base58: wallet::SecretString) -> Result<String, CommandError> {
+    wallet::public_key_from_private_base58(private_key_base58.expose()).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_file_path(app: tauri::AppHandle) -> Result<String, CommandError> {
     wallet::wallet_file_path(&app)
         .map(|p| p.display().to_string())
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 #[tauri::command]
-fn wallet_validate_address(address_base58: String) -> Result<bool, String> {
-    wallet::validate_public_key_base58(&address_base58).map_err(|e| e.to_string())
+fn wallet_validate_address(address_base58: String) -> wallet::PublicKeyValidation {
+    wallet::validate_public_key_base58(&address_base58)
 }
 
 #[tauri::command]
-fn wallet_change_password(app: tauri::AppHandle, args: ChangePasswordArgs) -> Result<(), String> {
+fn wallet_change_password(app: tauri::AppHandle, args: ChangePasswordArgs) -> Result<(), CommandError> {
     wallet::change_password(&app, &args.current_password, &args.new_password)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 #[tauri::command]
-fn wallet_sign_transaction(
+fn wallet_kdf_cost(app: tauri::AppHandle) -> Result<wallet::KdfCost, CommandError> {
+    wallet::kdf_cost(&app).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_set_kdf_preset(
     app: tauri::AppHandle,
     password: String,
+    preset: wallet::KdfPreset,
+) -> Result<(), CommandError> {
+    wallet::set_kdf_preset(&app, &password, preset).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_benchmark_kdf(preset: wallet::KdfPreset) -> Result<wallet::KdfBenchmark, CommandError> {
+    wallet::benchmark_kdf(preset).map_err(CommandError::from)
+}
+
+/// `password` may be omitted while the wallet is unlocked.
+#[tauri::command]
+fn wallet_sign_transaction(
+    app: tauri::AppHandle,
+    password: Option<String>,
     recipient_base58: String,
     amount: String,
     memo: String,
-) -> Result<String, String> {
+    nonce: Option<String>,
+) -> Result<String, CommandError> {
     wallet::sign_transaction(
         &app,
-        &password,
+        password.as_deref(),
         &recipient_base58,
         &amount,
         &memo,
+        nonce.as_deref(),
     )
-    .map_err(|e| e.to_string())
+    .map_err(CommandError::from)
 }
 
 #[tauri::command]
-async fn submit_transaction_to_network(api_url: String, signed_tx_json: String) -> Result<network::SubmitResult, String> {
-    network::submit_transaction_to_api(&api_url, &signed_tx_json)
+fn wallet_sign_contract_call(
+    app: tauri::AppHandle,
+    password: Option<String>,
+    contract: String,
+    function: String,
+    args: Vec<amadeus_tx::ContractArg>,
+    attachment: Option<amadeus_tx::Attachment>,
+    nonce: Option<String>,
+) -> Result<String, CommandError> {
+    wallet::sign_contract_call(
+        &app,
+        password.as_deref(),
+        &contract,
+        &function,
+        &args,
+        attachment.as_ref(),
+        nonce.as_deref(),
+    )
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_preview_transaction(
+    app: tauri::AppHandle,
+    public_key_base58: String,
+    recipient_base58: String,
+    amount: String,
+    memo: String,
+) -> Result<amadeus_tx::Transaction, CommandError> {
+    wallet::preview_transaction(&app, &public_key_base58, &recipient_base58, &amount, &memo)
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_nonce_status(app: tauri::AppHandle, address_base58: String) -> Result<wallet::NonceInfo, CommandError> {
+    wallet::nonce_status(&app, &address_base58).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn wallet_sync_nonce(
+    app: tauri::AppHandle,
+    api_url: String,
+    address_base58: String,
+) -> Result<wallet::NonceInfo, CommandError> {
+    wallet::sync_nonce(&app, &api_url, &address_base58)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_verify_transaction(signed_tx_base58: String) -> Result<amadeus_tx::SignedTx, CommandError> {
+    wallet::verify_signed_transaction(&signed_tx_base58).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_sign_message(
+    app: tauri::AppHandle,
+    password: Option<String>,
+    message: wallet::MessageInput,
+) -> Result<wallet::SignedMessage, CommandError> {
+    wallet::sign_message(&app, password.as_deref(), &message).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_verify_message(
+    public_key: String,
+    message: wallet::MessageInput,
+    signature: String,
+) -> Result<bool, CommandError> {
+    wallet::verify_message(&public_key, &message, &signature).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_export_unsigned_transfer(
+    app: tauri::AppHandle,
+    public_key_base58: String,
+    recipient_base58: String,
+    amount: String,
+    memo: String,
+    nonce: Option<String>,
+    path: String,
+) -> Result<amadeus_tx::Transaction, CommandError> {
+    wallet::export_unsigned_transfer(
+        &app,
+        &public_key_base58,
+        &recipient_base58,
+        &amount,
+        &memo,
+        nonce.as_deref(),
+        Path::new(&path),
+    )
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn wallet_export_unsigned_contract_call(
+    app: tauri::AppHandle,
+    public_key_base58: String,
+    contract: String,
+    function: String,
+    args: Vec<amadeus_tx::ContractArg>,
+    attachment: Option<amadeus_tx::Attachment>,
+    nonce: Option<String>,
+    path: String,
+) -> Result<amadeus_tx::Transaction, CommandError> {
+    wallet::export_unsigned_contract_call(
+        &app,
+        &public_key_base58,
+        &contract,
+        &function,
+        &args,
+        attachment.as_ref(),
+        nonce.as_deref(),
+        Path::new(&path),
+    )
+    .map_err(CommandError::from)
 }
 
 #[tauri::command]
-async fn check_node_health(api_url: String) -> Result<network::HealthCheckResult, String> {
-    Ok(network::check_node_health(&api_url).await)
+fn wallet_inspect_unsigned_transaction(path: String) -> Result<amadeus_tx::Transaction, CommandError> {
+    wallet::inspect_unsigned_transaction(Path::new(&path)).map_err(CommandError::from)
 }
 
 #[tauri::command]
-async fn get_balance(api_url: String, address_base58: String) -> Result<network::BalanceResult, String> {
-    network::get_balance_from_api(&api_url, &address_base58)
+fn wallet_sign_transaction_file(
+    app: tauri::AppHandle,
+    password: Option<String>,
+    input_path: String,
+    output_path: String,
+) -> Result<amadeus_tx::SignedTx, CommandError> {
+    wallet::sign_transaction_file(&app, password.as_deref(), Path::new(&input_path), Path::new(&output_path))
+        .map_err(CommandError::from)
+}
+
+/// `password` may be omitted while the wallet is unlocked.
+#[tauri::command]
+fn wallet_multisig_proof(app: tauri::AppHandle, password: Option<String>) -> Result<wallet::Cosigner, CommandError> {
+    wallet::multisig_proof(&app, password.as_deref()).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_multisig_address(cosigners: Vec<wallet::Cosigner>) -> Result<String, CommandError> {
+    wallet::multisig_address(&cosigners).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_multisig_create_transfer(
+    app: tauri::AppHandle,
+    cosigners: Vec<wallet::Cosigner>,
+    recipient_base58: String,
+    amount: String,
+    memo: String,
+    nonce: Option<String>,
+) -> Result<wallet::PartialTx, CommandError> {
+    wallet::create_multisig_transfer(&app, &cosigners, &recipient_base58, &amount, &memo, nonce.as_deref())
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_multisig_sign(
+    app: tauri::AppHandle,
+    password: Option<String>,
+    partial: wallet::PartialTx,
+) -> Result<wallet::PartialTx, CommandError> {
+    wallet::sign_multisig(&app, password.as_deref(), &partial).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_multisig_merge(partials: Vec<wallet::PartialTx>) -> Result<wallet::PartialTx, CommandError> {
+    wallet::merge_multisig(&partials).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn wallet_multisig_status(partial: wallet::PartialTx) -> Result<wallet::MultisigStatus, CommandError> {
+    wallet::multisig_status(&partial).map_err(CommandError::from)
+}
+
+/// n-of-n: fails until every co-signer has signed.
+#[tauri::command]
+fn wallet_multisig_finalize(partial: wallet::PartialTx) -> Result<String, CommandError> {
+    wallet::finalize_multisig(&partial).map_err(CommandError::from)
+}
+
+#[tauri::command]
+fn vecpak_decode(packed_base58: String) -> Result<amadeus_tx::Term, CommandError> {
+    let bytes = bs58::decode(packed_base58.trim())
+        .into_vec()
+        .map_err(|e| wallet::WalletError::InvalidBase58(e.to_string()))?;
+    Ok(amadeus_tx::decode(&bytes).map_err(wallet::WalletError::from)?)
+}
+
+fn network_settings_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, network::NetworkError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| network::NetworkError::Settings(e.to_string()))?;
+    Ok(dir.join("network.json"))
+}
+
+#[tauri::command]
+fn get_network_settings(http: tauri::State<'_, network::HttpClient>) -> network::HttpSettings {
+    http.settings()
+}
+
+/// Why requests are refused, if the saved settings could not be loaded or built. Cleared by a
+/// successful `set_network_settings`.
+#[tauri::command]
+fn get_network_settings_error(http: tauri::State<'_, network::HttpClient>) -> Option<network::NetworkError> {
+    http.settings_error()
+}
+
+/// Rebuilds the shared HTTP client and saves the settings; invalid settings change nothing.
+#[tauri::command]
+fn set_network_settings(
+    app: tauri::AppHandle,
+    http: tauri::State<'_, network::HttpClient>,
+    settings: network::HttpSettings,
+) -> Result<(), CommandError> {
+    http.configure(settings.clone())?;
+    settings.save(&network_settings_path(&app)?).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn submit_transaction_to_network(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+    signed_tx_json: String,
+) -> Result<network::SubmitResult, CommandError> {
+    network::submit_transaction_to_api(&http, &api_url, &signed_tx_json)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Submits a signed transaction file written by `wallet_sign_transaction_file`.
+#[tauri::command]
+async fn broadcast_transaction_file(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+    path: String,
+) -> Result<network::SubmitResult, CommandError> {
+    let signed_tx_base58 = wallet::load_signed_transaction_file(Path::new(&path))?;
+    network::submit_transaction_to_api(&http, &api_url, &signed_tx_base58)
+        .await
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn check_node_health(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+) -> Result<network::HealthCheckResult, CommandError> {
+    Ok(network::check_node_health(&http, &api_url).await)
+}
+
+#[tauri::command]
+fn get_node_pool_status(http: tauri::State<'_, network::HttpClient>, api_url: String) -> network::NodePoolStatus {
+    http.node_status(&api_url)
+}
+
+#[tauri::command]
+async fn check_node_pool(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+) -> Result<network::NodePoolStatus, CommandError> {
+    Ok(network::check_node_pool(&http, &api_url).await)
+}
+
+#[tauri::command]
+async fn get_balance(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+    address_base58: String,
+) -> Result<network::BalanceResult, CommandError> {
+    network::get_balance_from_api(&http, &api_url, &address_base58)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 #[tauri::command]
 async fn get_transactions(
+    http: tauri::State<'_, network::HttpClient>,
     api_url: String,
     address_base58: String,
     sent_cursor: Option<String>,
     received_cursor: Option<String>,
-) -> Result<network::TransactionsResult, String> {
+) -> Result<network::TransactionsResult, CommandError> {
     network::get_transactions_from_api(
+        &http,
         &api_url,
         &address_base58,
         sent_cursor.as_deref(),
         received_cursor.as_deref(),
     )
     .await
-    .map_err(|e| e.to_string())
+    .map_err(CommandError::from)
 }
 
 #[tauri::command]
-async fn get_transaction_status(api_url: String, tx_hash: String) -> Result<network::TransactionStatusResult, String> {
-    network::get_transaction_status_from_api(&api_url, &tx_hash)
+async fn get_transaction_status(
+    http: tauri::State<'_, network::HttpClient>,
+    api_url: String,
+    tx_hash: String,
+) -> Result<network::TransactionStatusResult, CommandError> {
+    network::get_transaction_status_from_api(&http, &api_url, &tx_hash)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(wallet::Session::default())
+        .setup(|app| {
+            let settings =
+                network_settings_path(app.handle()).and_then(|path| network::HttpSettings::load(&path));
+            app.manage(network::HttpClient::with_saved(settings));
+            wallet::spawn_auto_lock(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             wallet_create,
             wallet_import,
+            wallet_export_mnemonic,
+            wallet_import_mnemonic,
+            wallet_split_key,
+            wallet_recover_key,
+            wallet_export_backup,
+            wallet_import_backup,
+            wallet_derive_account,
+            wallet_scan_accounts,
+            wallet_list_accounts,
+            wallet_rename_account,
+            wallet_select_account,
+            wallet_delete_account,
             wallet_unlock,
+            wallet_lock,
+            wallet_session_status,
+            wallet_set_auto_lock,
             wallet_has,
             wallet_public_key_from_private,
             wallet_file_path,
             wallet_validate_address,
             wallet_change_password,
+            wallet_kdf_cost,
+            wallet_set_kdf_preset,
+            wallet_benchmark_kdf,
             wallet_sign_transaction,
+            wallet_sign_contract_call,
+            wallet_preview_transaction,
+            wallet_verify_transaction,
+            wallet_nonce_status,
+            wallet_sync_nonce,
+            wallet_sign_message,
+            wallet_verify_message,
+            wallet_export_unsigned_transfer,
+            wallet_export_unsigned_contract_call,
+            wallet_inspect_unsigned_transaction,
+            wallet_sign_transaction_file,
+            wallet_multisig_proof,
+            wallet_multisig_address,
+            wallet_multisig_create_transfer,
+            wallet_multisig_sign,
+            wallet_multisig_merge,
+            wallet_multisig_status,
+            wallet_multisig_finalize,
+            vecpak_decode,
+            get_network_settings,
+            get_network_settings_error,
+            set_network_settings,
             submit_transaction_to_network,
+            broadcast_transaction_file,
             check_node_health,
+            get_node_pool_status,
+            check_node_pool,
             get_balance,
             get_transactions,
             get_transaction_status,
@@ -1,104 +1,770 @@
+use crate::amadeus_tx::SignedTx;
+use crate::amount::{format_amount, parse_amount};
+use crate::wallet;
+use rand::Rng;
 use serde_json::Value;
 
-const REQUEST_TIMEOUT_SECS: u64 = 30;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// What went wrong talking to a node. Serialized as `{ code, message, details? }` so the UI can
+/// branch on `code` rather than on the wording of `message`.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum NetworkError {
+    #[error("Request to the node timed out")]
+    Timeout,
+
+    #[error("Could not resolve the node's address: {0}")]
+    Dns(String),
+
+    #[error("Could not connect to the node: {0}")]
+    Connect(String),
+
+    #[error("Secure connection to the node failed: {0}")]
+    Tls(String),
+
+    /// Connected, but sending the request or reading the response broke off.
+    #[error("Connection to the node was interrupted: {0}")]
+    Interrupted(String),
+
+    #[error("Too many redirects or a redirect loop: {0}")]
+    Redirect(String),
+
+    #[error("HTTP {status}{}", http_detail(.status, .body))]
+    Http { status: u16, body: String },
+
+    #[error("HTTP {status} (Cloudflare: {})", cloudflare_hint(.status))]
+    Cloudflare { status: u16 },
+
+    #[error("Unexpected response from the node: {0}")]
+    Decode(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("Invalid node URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Invalid network settings: {0}")]
+    Settings(String),
+}
+
+impl NetworkError {
+    /// Stable identifier for the frontend; never reword these.
+    pub fn code(&self) -> &'static str {
+        match self {
+            NetworkError::Timeout => "timeout",
+            NetworkError::Dns(_) => "dns",
+            NetworkError::Connect(_) => "connect",
+            NetworkError::Tls(_) => "tls",
+            NetworkError::Interrupted(_) => "interrupted",
+            NetworkError::Redirect(_) => "redirect",
+            NetworkError::Http { .. } => "http_status",
+            NetworkError::Cloudflare { .. } => "cloudflare",
+            NetworkError::Decode(_) => "decode",
+            NetworkError::NotFound(_) => "not_found",
+            NetworkError::InvalidUrl(_) => "invalid_url",
+            NetworkError::InvalidTransaction(_) => "invalid_transaction",
+            NetworkError::Settings(_) => "settings",
+        }
+    }
+
+    /// The node could not be reached at all, as opposed to answering with an error.
+    pub fn is_connectivity(&self) -> bool {
+        matches!(
+            self,
+            NetworkError::Timeout | NetworkError::Dns(_) | NetworkError::Connect(_) | NetworkError::Tls(_)
+        )
+    }
+
+    /// Worth trying again later: the node was unreachable, overloaded or behind a failing proxy.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            NetworkError::Timeout
+            | NetworkError::Dns(_)
+            | NetworkError::Connect(_)
+            | NetworkError::Interrupted(_)
+            | NetworkError::Cloudflare { .. } => true,
+            NetworkError::Http { status, .. } => matches!(status, 408 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+
+    /// The HTTP status (and the node's error text) for the codes that have one.
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            NetworkError::Http { status, body } => Some(serde_json::json!({ "status": status, "body": body })),
+            NetworkError::Cloudflare { status } => Some(serde_json::json!({ "status": status })),
+            _ => None,
+        }
+    }
+
+    /// A non-success response; `body` is the node's error text, if any.
+    pub fn from_status(status: u16, body: &str) -> Self {
+        let body = body.trim();
+        match status {
+            404 if body.is_empty() => NetworkError::NotFound("HTTP 404".to_string()),
+            404 => NetworkError::NotFound(format!("HTTP 404: {}", body)),
+            520..=527 => NetworkError::Cloudflare { status },
+            _ => NetworkError::Http {
+                status,
+                body: body.to_string(),
+            },
+        }
+    }
+}
+
+impl From<reqwest::Error> for NetworkError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return NetworkError::Timeout;
+        }
+        if e.is_builder() {
+            return NetworkError::InvalidUrl(root_cause(&e));
+        }
+        if e.is_decode() {
+            return NetworkError::Decode(root_cause(&e));
+        }
+        if e.is_redirect() {
+            return NetworkError::Redirect(root_cause(&e));
+        }
+        if e.is_connect() {
+            return connect_error(&e);
+        }
+        // `is_request` is also set for connect errors, so it is only checked after them.
+        if e.is_body() || e.is_request() {
+            return NetworkError::Interrupted(root_cause(&e));
+        }
+        NetworkError::Connect(root_cause(&e))
+    }
+}
+
+/// Tells DNS and TLS failures apart from other connect errors, by type: rustls errors reach us
+/// either directly or wrapped in an `io::Error`, and lookups fail with `DnsError` from
+/// `SystemResolver`.
+fn connect_error(e: &reqwest::Error) -> NetworkError {
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        let tls = cause.is::<rustls::Error>()
+            || cause
+                .downcast_ref::<std::io::Error>()
+                .and_then(|io| io.get_ref())
+                .is_some_and(|inner| inner.is::<rustls::Error>());
+        if tls {
+            return NetworkError::Tls(root_cause(e));
+        }
+        if cause.is::<DnsError>() {
+            return NetworkError::Dns(root_cause(e));
+        }
+        source = cause.source();
+    }
+    NetworkError::Connect(root_cause(e))
+}
+
+/// The system resolver, wrapped so a failed lookup has a type of its own; the default resolver
+/// reports it as a plain `io::Error`, like any other connect failure.
+struct SystemResolver;
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+struct DnsError(std::io::Error);
+
+impl reqwest::dns::Resolve for SystemResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            // The port is filled in by the connector.
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await.map_err(DnsError)?;
+            Ok(Box::new(addrs) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Same shape as the errors commands reject with.
+impl serde::Serialize for NetworkError {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&crate::error::CommandError::from(self.clone()), s)
+    }
+}
+
+/// The innermost cause, which says more than reqwest's "error sending request for url".
+fn root_cause(e: &(dyn std::error::Error + 'static)) -> String {
+    let mut cause = e;
+    while let Some(next) = cause.source() {
+        cause = next;
+    }
+    cause.to_string()
+}
+
+fn http_detail(status: &u16, body: &str) -> String {
+    let hint = match status {
+        502..=504 => " (server temporarily unavailable — try again later)",
+        500..=599 => " (server error — try again later)",
+        _ => "",
+    };
+    if body.is_empty() {
+        hint.to_string()
+    } else {
+        format!(": {}{}", body, hint)
+    }
+}
+
+fn cloudflare_hint(status: &u16) -> &'static str {
+    match status {
+        520 => "origin server error — node may be down or overloaded",
+        521 => "origin server refused connection",
+        522 => "connection timed out",
+        523 => "origin unreachable",
+        524 => "timeout",
+        _ => "origin error",
+    }
+}
+
+/// How the shared HTTP client is built; kept in `network.json` in the app data directory.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    pub request_timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    /// `check_node_health` gives up sooner than other requests.
+    pub health_timeout_secs: u64,
+    /// How long an unused keep-alive connection stays in the pool.
+    pub pool_idle_timeout_secs: u64,
+    pub pool_max_idle_per_host: usize,
+    pub user_agent: String,
+    /// An `http://`, `https://`, `socks5://` or `socks5h://` URL for all requests; `None` uses the
+    /// system proxy settings.
+    pub proxy: Option<String>,
+    /// PEM certificates (or bundles) to trust on top of the built-in roots, e.g. an internal CA.
+    pub ca_certificates: Vec<String>,
+    /// Nodes to fail over to. The `api_url` a command is given is always a candidate too, with
+    /// priority 0 unless it is listed here.
+    pub nodes: Vec<NodeEndpoint>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            request_timeout_secs: 30,
+            connect_timeout_secs: 10,
+            health_timeout_secs: 10,
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 8,
+            user_agent: format!("amadeus-wallet/{}", env!("CARGO_PKG_VERSION")),
+            proxy: None,
+            ca_certificates: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl HttpSettings {
+    fn build_client(&self) -> Result<reqwest::Client, NetworkError> {
+        if self.request_timeout_secs == 0 || self.connect_timeout_secs == 0 || self.health_timeout_secs == 0 {
+            return Err(NetworkError::Settings("Timeouts must be at least one second".to_string()));
+        }
+        let mut builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.request_timeout_secs))
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .pool_idle_timeout(Duration::from_secs(self.pool_idle_timeout_secs))
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .user_agent(self.user_agent.trim())
+            .dns_resolver(std::sync::Arc::new(SystemResolver));
+        if let Some(proxy) = self.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| NetworkError::Settings(format!("Invalid proxy: {}", e)))?;
+            builder = builder.proxy(proxy);
+        }
+        for (i, pem) in self.ca_certificates.iter().enumerate() {
+            let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+                .map_err(|e| NetworkError::Settings(format!("Invalid CA certificate {}: {}", i + 1, e)))?;
+            if certs.is_empty() {
+                return Err(NetworkError::Settings(format!(
+                    "Invalid CA certificate {}: no PEM certificate found",
+                    i + 1
+                )));
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        for node in &self.nodes {
+            let url = normalize_url(&node.url);
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(NetworkError::Settings(format!(
+                    "Invalid node URL \"{}\": must start with http:// or https://",
+                    node.url
+                )));
+            }
+        }
+        builder.build().map_err(|e| NetworkError::Settings(root_cause(&e)))
+    }
+
+    /// A missing file means the defaults. A file that cannot be read or parsed is an error rather
+    /// than the defaults: those would quietly drop a configured proxy.
+    pub fn load(path: &Path) -> Result<Self, NetworkError> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HttpSettings::default()),
+            Err(e) => return Err(NetworkError::Settings(format!("Could not read {}: {}", path.display(), e))),
+        };
+        serde_json::from_slice(&data)
+            .map_err(|e| NetworkError::Settings(format!("Could not parse {}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), NetworkError> {
+        let data = serde_json::to_vec_pretty(self).map_err(|e| NetworkError::Settings(e.to_string()))?;
+        wallet::write_atomic(path, &data).map_err(|e| NetworkError::Settings(format!("Could not save: {}", e)))
+    }
+}
+
+/// The one HTTP client every request goes through, held in Tauri state so its connection pool
+/// (and TLS sessions) are reused across calls.
+///
+/// If the saved settings cannot be loaded or built (e.g. a certificate file that went bad), the
+/// app still starts but every request fails with that error until the settings are fixed. Falling
+/// back to the defaults instead would bypass a configured proxy without the user knowing.
+pub struct HttpClient {
+    inner: RwLock<(HttpSettings, Result<reqwest::Client, NetworkError>)>,
+    nodes: NodePool,
+}
+
+impl HttpClient {
+    pub fn new(settings: HttpSettings) -> Self {
+        let client = settings.build_client();
+        HttpClient {
+            inner: RwLock::new((settings, client)),
+            nodes: NodePool::default(),
+        }
+    }
+
+    /// Starts from the saved settings as `HttpSettings::load` returned them. If they could not be
+    /// loaded, the file is left alone for the user to fix and the defaults are shown meanwhile,
+    /// but nothing is sent.
+    pub fn with_saved(settings: Result<HttpSettings, NetworkError>) -> Self {
+        match settings {
+            Ok(settings) => Self::new(settings),
+            Err(error) => HttpClient {
+                inner: RwLock::new((HttpSettings::default(), Err(error))),
+                nodes: NodePool::default(),
+            },
+        }
+    }
+
+    /// Cheap: clones share the pool. Fails while the settings are invalid.
+    pub fn client(&self) -> Result<reqwest::Client, NetworkError> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).1.clone()
+    }
+
+    pub fn settings(&self) -> HttpSettings {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).0.clone()
+    }
+
+    /// Why no requests are being sent, if the saved settings are invalid.
+    pub fn settings_error(&self) -> Option<NetworkError> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).1.as_ref().err().cloned()
+    }
+
+    /// Rebuilds the client; on error the current one stays in place.
+    pub fn configure(&self, settings: HttpSettings) -> Result<(), NetworkError> {
+        let client = settings.build_client()?;
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = (settings, Ok(client));
+        Ok(())
+    }
+
+    /// `api_url` and the configured nodes, best first.
+    fn candidates(&self, api_url: &str) -> Vec<(String, u32)> {
+        self.nodes.candidates(api_url, &self.settings().nodes)
+    }
+
+    /// The node a request for `api_url` goes to first.
+    fn best_node(&self, api_url: &str) -> String {
+        self.candidates(api_url)
+            .into_iter()
+            .next()
+            .map_or_else(|| normalize_url(api_url), |(url, _)| url)
+    }
+
+    pub fn node_status(&self, api_url: &str) -> NodePoolStatus {
+        self.nodes.status(&self.candidates(api_url))
+    }
+
+    /// GETs `path` from the best node, moving on to the next one on a network error or 5xx (e.g. a
+    /// Cloudflare 52x page). When every node fails, the last one's response or error is returned.
+    async fn get_with_failover(&self, api_url: &str, path: &str) -> Result<reqwest::Response, NetworkError> {
+        let client = self.client()?;
+        let candidates = self.candidates(api_url);
+        let Some(((last, _), rest)) = candidates.split_last() else {
+            return Err(NetworkError::InvalidUrl("no node configured".into()));
+        };
+        for (base, _) in rest {
+            let started = Instant::now();
+            let result = client.get(format!("{}{}", base, path)).send().await;
+            self.nodes.record(base, started, &result);
+            if let Ok(res) = result {
+                if !res.status().is_server_error() {
+                    return Ok(res);
+                }
+            }
+        }
+        let started = Instant::now();
+        let result = client.get(format!("{}{}", last, path)).send().await;
+        self.nodes.record(last, started, &result);
+        Ok(result?)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(HttpSettings::default())
+    }
+}
+
+/// A node the wallet may use; lower `priority` is preferred.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NodeEndpoint {
+    pub url: String,
+    #[serde(default)]
+    pub priority: u32,
+}
+
+/// How long a node that just failed is passed over before it gets another chance.
+const NODE_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+struct NodeHealth {
+    healthy: Option<bool>,
+    /// Smoothed over recent requests.
+    latency_ms: Option<u64>,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_checked_ms: Option<u64>,
+    down_until: Option<Instant>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NodeStatus {
+    pub url: String,
+    pub priority: u32,
+    /// `None` until the node has been checked or used.
+    pub healthy: Option<bool>,
+    pub latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_checked_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NodePoolStatus {
+    /// The node that last answered, or the one that will be tried first.
+    pub current: Option<String>,
+    /// Best first, in the order requests try them.
+    pub nodes: Vec<NodeStatus>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    health: HashMap<String, NodeHealth>,
+    current: Option<String>,
+}
+
+/// Health and latency of every node seen, keyed by URL; which nodes exist comes from the settings.
+#[derive(Default)]
+struct NodePool {
+    state: Mutex<PoolState>,
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+impl NodePool {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Nodes that are not cooling down after a failure come first, then by priority, then by
+    /// latency (unmeasured nodes after measured ones).
+    fn candidates(&self, api_url: &str, nodes: &[NodeEndpoint]) -> Vec<(String, u32)> {
+        let primary = normalize_url(api_url);
+        let mut list: Vec<(String, u32)> = Vec::new();
+        if !primary.is_empty() {
+            let priority = nodes
+                .iter()
+                .find(|n| normalize_url(&n.url) == primary)
+                .map_or(0, |n| n.priority);
+            list.push((primary, priority));
+        }
+        for node in nodes {
+            let url = normalize_url(&node.url);
+            if !url.is_empty() && !list.iter().any(|(u, _)| *u == url) {
+                list.push((url, node.priority));
+            }
+        }
+        let state = self.lock();
+        let now = Instant::now();
+        list.sort_by_key(|(url, priority)| {
+            let health = state.health.get(url);
+            let cooling_down = health.and_then(|h| h.down_until).is_some_and(|t| t > now);
+            let latency = health.and_then(|h| h.latency_ms).unwrap_or(u64::MAX);
+            (cooling_down, *priority, latency)
+        });
+        list
+    }
+
+    fn record_success(&self, url: &str, latency: Duration) {
+        let mut state = self.lock();
+        let health = state.health.entry(url.to_string()).or_default();
+        let sample = latency.as_millis() as u64;
+        health.latency_ms = Some(health.latency_ms.map_or(sample, |old| (old * 3 + sample) / 4));
+        health.healthy = Some(true);
+        health.consecutive_failures = 0;
+        health.last_error = None;
+        health.last_checked_ms = Some(now_ms());
+        health.down_until = None;
+        state.current = Some(url.to_string());
+    }
+
+    fn record_failure(&self, url: &str, error: String) {
+        let mut state = self.lock();
+        let health = state.health.entry(url.to_string()).or_default();
+        health.healthy = Some(false);
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+        health.last_checked_ms = Some(now_ms());
+        health.down_until = Some(Instant::now() + NODE_COOLDOWN);
+    }
+
+    /// A 5xx counts against the node; any other response means it is up.
+    fn record(&self, url: &str, started: Instant, result: &Result<reqwest::Response, reqwest::Error>) {
+        match result {
+            Ok(res) if res.status().is_server_error() => {
+                self.record_failure(url, NetworkError::from_status(res.status().as_u16(), "").to_string())
+            }
+            Ok(_) => self.record_success(url, started.elapsed()),
+            Err(e) => self.record_failure(url, root_cause(e)),
+        }
+    }
+
+    fn status(&self, candidates: &[(String, u32)]) -> NodePoolStatus {
+        let state = self.lock();
+        let current = state
+            .current
+            .clone()
+            .filter(|c| candidates.iter().any(|(url, _)| url == c))
+            .or_else(|| candidates.first().map(|(url, _)| url.clone()));
+        let nodes = candidates
+            .iter()
+            .map(|(url, priority)| {
+                let health = state.health.get(url);
+                NodeStatus {
+                    url: url.clone(),
+                    priority: *priority,
+                    healthy: health.and_then(|h| h.healthy),
+                    latency_ms: health.and_then(|h| h.latency_ms),
+                    consecutive_failures: health.map_or(0, |h| h.consecutive_failures),
+                    last_error: health.and_then(|h| h.last_error.clone()),
+                    last_checked_ms: health.and_then(|h| h.last_checked_ms),
+                }
+            })
+            .collect();
+        NodePoolStatus { current, nodes }
+    }
+}
+
+/// How a submission ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmitOutcome {
+    /// The node accepted the transaction.
+    Accepted,
+    /// An earlier attempt landed even though its response was lost; the transaction was found on
+    /// chain by its hash.
+    AlreadySubmitted,
+    /// The node refused the transaction; sending it again will not help.
+    Rejected,
+    /// A retry was refused but an earlier attempt may still be pending, so the transaction may yet
+    /// land. Its status should be checked by hash before anything else is sent.
+    Unknown,
+    /// Every attempt failed on a transient error and the transaction was not found on chain,
+    /// though one of them may still be pending. It can be resubmitted as is: it keeps the same hash
+    /// and nonce, so it cannot apply twice.
+    Failed,
+}
 
 #[derive(Debug, serde::Serialize)]
 pub struct SubmitResult {
     pub ok: bool,
+    pub outcome: SubmitOutcome,
+    /// Computed locally, so it is known even when the node never answered.
     pub tx_hash: Option<String>,
-    pub error: Option<String>,
+    pub error: Option<NetworkError>,
     pub network_error: bool,
+    pub attempts: u32,
+}
+
+const SUBMIT_MAX_ATTEMPTS: u32 = 5;
+const SUBMIT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const SUBMIT_MAX_BACKOFF: Duration = Duration::from_secs(16);
+
+/// Submits a signed transaction, retrying with exponential backoff and jitter on network errors,
+/// 408, 429 and 5xx (e.g. Cloudflare 52x). Before every retry, and before giving up, the node is
+/// asked for the transaction by its hash so one whose response was lost is not sent again.
+pub async fn submit_transaction_to_api(
+    http: &HttpClient,
+    api_url: &str,
+    signed_tx_base58: &str,
+) -> Result<SubmitResult, NetworkError> {
+    let packed = bs58::decode(signed_tx_base58.trim())
+        .into_vec()
+        .map_err(|e| NetworkError::InvalidTransaction(e.to_string()))?;
+    let hash = SignedTx::hash_of(&packed).map_err(|e| NetworkError::InvalidTransaction(e.to_string()))?;
+    let tx_hash = bs58::encode(hash).into_string();
+    // Invalid settings would otherwise look like a rejection after the first attempt.
+    http.client()?;
+
+    let mut attempts = 0;
+    let mut last_error = None;
+    while attempts < SUBMIT_MAX_ATTEMPTS {
+        if attempts > 0 {
+            tokio::time::sleep(submit_backoff(attempts)).await;
+            if is_on_chain(http, api_url, &tx_hash).await {
+                return Ok(SubmitResult::new(SubmitOutcome::AlreadySubmitted, tx_hash, None, attempts));
+            }
+        }
+        attempts += 1;
+        let error = match submit_once(http, api_url, signed_tx_base58.trim()).await {
+            Ok(node_hash) => {
+                let hash = node_hash.unwrap_or(tx_hash);
+                return Ok(SubmitResult::new(SubmitOutcome::Accepted, hash, None, attempts));
+            }
+            Err(error) => error,
+        };
+        if !error.is_transient() {
+            if attempts == 1 {
+                return Ok(SubmitResult::new(SubmitOutcome::Rejected, tx_hash, Some(error), attempts));
+            }
+            // A retry can be refused as a duplicate when an earlier attempt reached the node, which
+            // may not have included it yet.
+            if is_on_chain(http, api_url, &tx_hash).await {
+                return Ok(SubmitResult::new(SubmitOutcome::AlreadySubmitted, tx_hash, None, attempts));
+            }
+            return Ok(SubmitResult::new(SubmitOutcome::Unknown, tx_hash, Some(error), attempts));
+        }
+        last_error = Some(error);
+    }
+
+    if is_on_chain(http, api_url, &tx_hash).await {
+        return Ok(SubmitResult::new(SubmitOutcome::AlreadySubmitted, tx_hash, None, attempts));
+    }
+    Ok(SubmitResult::new(SubmitOutcome::Failed, tx_hash, last_error, attempts))
+}
+
+impl SubmitResult {
+    fn new(outcome: SubmitOutcome, tx_hash: String, error: Option<NetworkError>, attempts: u32) -> Self {
+        SubmitResult {
+            ok: matches!(outcome, SubmitOutcome::Accepted | SubmitOutcome::AlreadySubmitted),
+            outcome,
+            tx_hash: Some(tx_hash),
+            network_error: error.as_ref().is_some_and(NetworkError::is_connectivity),
+            error,
+            attempts,
+        }
+    }
+}
+
+/// Doubles from `SUBMIT_INITIAL_BACKOFF` up to `SUBMIT_MAX_BACKOFF`, then picks a random delay in
+/// the upper half so wallets that failed together do not retry together.
+fn submit_backoff(retry: u32) -> Duration {
+    let exp = SUBMIT_INITIAL_BACKOFF
+        .saturating_mul(1 << retry.saturating_sub(1).min(16))
+        .min(SUBMIT_MAX_BACKOFF);
+    let half = exp.as_millis() as u64 / 2;
+    Duration::from_millis(half + rand::rng().random_range(0..=half))
+}
+
+/// A failed lookup counts as "not found": resubmitting is the safe default.
+async fn is_on_chain(http: &HttpClient, api_url: &str, tx_hash: &str) -> bool {
+    get_transaction_status_from_api(http, api_url, tx_hash)
+        .await
+        .is_ok_and(|r| r.ok && r.status.is_some())
 }
 
-pub async fn submit_transaction_to_api(api_url: &str, signed_tx_base58: &str) -> Result<SubmitResult, String> {
-    let base = api_url.trim_end_matches('/');
+/// One POST to the best node; returns the hash the node reported, if any.
+async fn submit_once(
+    http: &HttpClient,
+    api_url: &str,
+    signed_tx_base58: &str,
+) -> Result<Option<String>, NetworkError> {
+    // A POST is not retried on another node here: it may have landed even if the response was lost.
+    let base = http.best_node(api_url);
     let url = format!("{}/api/tx/submit", base);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| e.to_string())?;
+    let client = http.client()?;
 
-    let res = match client
+    let started = Instant::now();
+    let result = client
         .post(&url)
         .header("Content-Type", "text/plain")
         .body(signed_tx_base58.to_string())
         .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            let msg = e.to_string();
-            return Ok(SubmitResult {
-                ok: false,
-                tx_hash: None,
-                error: Some(msg),
-                network_error: true,
-            });
-        }
-    };
+        .await;
+    http.nodes.record(&base, started, &result);
+    let res = result?;
 
     let status = res.status();
-    let text = res.text().await.map_err(|e| e.to_string())?;
-
-    let ok = status.is_success();
-    let mut tx_hash: Option<String> = None;
-    let mut error: Option<String> = None;
-
-    if !text.is_empty() {
-        if let Ok(data) = serde_json::from_str::<Value>(&text) {
-            tx_hash = data
-                .get("txHash")
-                .or_else(|| data.get("tx_hash"))
-                .or_else(|| data.get("hash"))
-                .or_else(|| data.get("id"))
-                .and_then(|v| v.as_str())
-                .map(String::from);
-            if !ok {
-                error = data
-                    .get("error")
-                    .or_else(|| data.get("message"))
-                    .or_else(|| data.get("detail"))
-                    .and_then(|v| v.as_str())
-                    .map(String::from);
-            }
-        }
-    }
-    if error.is_none() && !ok {
-        let status_code = status.as_u16();
-        let hint = match status_code {
-            520 => " (Cloudflare: origin server error — node may be down or overloaded)",
-            521 => " (Cloudflare: origin server refused connection)",
-            522 => " (Cloudflare: connection timed out)",
-            523 => " (Cloudflare: origin unreachable)",
-            524 => " (Cloudflare: timeout)",
-            502 | 503 | 504 => " (server temporarily unavailable — try again later)",
-            _ if (500..600).contains(&status_code) => " (server error — try again later)",
-            _ => "",
-        };
-        error = Some(if text.trim().is_empty() {
-            format!("HTTP {}{}", status_code, hint)
-        } else {
-            format!("HTTP {}: {}{}", status_code, text.trim(), hint)
-        });
-    }
+    let text = res.text().await?;
 
-    Ok(SubmitResult {
-        ok,
-        tx_hash,
-        error,
-        network_error: false,
-    })
+    let data = serde_json::from_str::<Value>(&text).ok();
+    if !status.is_success() {
+        let message = data
+            .as_ref()
+            .and_then(|d| d.get("error").or_else(|| d.get("message")).or_else(|| d.get("detail")))
+            .and_then(|v| v.as_str())
+            .unwrap_or(&text);
+        return Err(NetworkError::from_status(status.as_u16(), message));
+    }
+    Ok(data.and_then(|d| {
+        d.get("txHash")
+            .or_else(|| d.get("tx_hash"))
+            .or_else(|| d.get("hash"))
+            .or_else(|| d.get("id"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }))
 }
 
 #[derive(Debug, serde::Serialize)]
 pub struct BalanceResult {
     pub ok: bool,
     pub balance_flat: Option<String>,
-    pub error: Option<String>,
+    pub balance: Option<String>,
+    pub error: Option<NetworkError>,
     pub network_error: bool,
 }
 
+impl BalanceResult {
+    fn failed(error: NetworkError) -> Self {
+        BalanceResult {
+            ok: false,
+            balance_flat: None,
+            balance: None,
+            network_error: error.is_connectivity(),
+            error: Some(error),
+        }
+    }
+}
+
 fn balance_value_to_string(v: &Value) -> Option<String> {
     v.as_str()
         .map(String::from)
@@ -132,52 +798,31 @@ fn parse_balance_from_json(data: &Value) -> Option<String> {
         })
 }
 
-pub async fn get_balance_from_api(api_url: &str, address_base58: &str) -> Result<BalanceResult, String> {
-    let base = api_url.trim_end_matches('/');
+pub async fn get_balance_from_api(
+    http: &HttpClient,
+    api_url: &str,
+    address_base58: &str,
+) -> Result<BalanceResult, NetworkError> {
     let addr = address_base58.trim();
-    let url = format!("{}/api/wallet/balance/{}", base, addr);
+    let path = format!("/api/wallet/balance/{}", addr);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let res = match client.get(&url).send().await {
+    let res = match http.get_with_failover(api_url, &path).await {
         Ok(r) => r,
-        Err(e) => {
-            let msg = e.to_string();
-            let network_error = msg.contains("connection") || msg.contains("dns") || msg.contains("timed out");
-            return Ok(BalanceResult {
-                ok: false,
-                balance_flat: None,
-                error: Some(msg),
-                network_error,
-            });
-        }
+        Err(e) => return Ok(BalanceResult::failed(e.into())),
     };
 
     let status = res.status();
-    let code = status.as_u16();
-    let text = res.text().await.map_err(|e| e.to_string())?;
+    let text = res.text().await?;
 
     if !status.is_success() {
-        let err_msg = if text.trim().is_empty() {
-            format!("HTTP {}", code)
-        } else {
-            format!("HTTP {}: {}", code, text.trim())
-        };
-        return Ok(BalanceResult {
-            ok: false,
-            balance_flat: None,
-            error: Some(err_msg),
-            network_error: false,
-        });
+        return Ok(BalanceResult::failed(NetworkError::from_status(status.as_u16(), &text)));
     }
 
     if let Ok(data) = serde_json::from_str::<Value>(&text) {
         if let Some(balance_flat) = parse_balance_from_json(&data) {
             return Ok(BalanceResult {
                 ok: true,
+                balance: display_amount(&balance_flat),
                 balance_flat: Some(balance_flat),
                 error: None,
                 network_error: false,
@@ -185,12 +830,9 @@ pub async fn get_balance_from_api(api_url: &str, address_base58: &str) -> Result
         }
     }
 
-    Ok(BalanceResult {
-        ok: false,
-        balance_flat: None,
-        error: Some("Response has no balance field".to_string()),
-        network_error: false,
-    })
+    Ok(BalanceResult::failed(NetworkError::Decode(
+        "Response has no balance field".to_string(),
+    )))
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -200,6 +842,7 @@ pub struct TransactionItem {
     pub from_address: Option<String>,
     pub to_address: Option<String>,
     pub amount_flat: String,
+    pub amount: Option<String>,
     pub block_height: Option<u64>,
     pub status: Option<String>,
     pub memo: Option<String>,
@@ -213,18 +856,42 @@ pub struct TransactionsResult {
     pub transactions: Vec<TransactionItem>,
     pub next_sent_cursor: Option<String>,
     pub next_received_cursor: Option<String>,
-    pub error: Option<String>,
+    pub error: Option<NetworkError>,
     pub network_error: bool,
 }
 
+impl TransactionsResult {
+    fn failed(error: NetworkError) -> Self {
+        TransactionsResult {
+            ok: false,
+            transactions: vec![],
+            next_sent_cursor: None,
+            next_received_cursor: None,
+            network_error: error.is_connectivity(),
+            error: Some(error),
+        }
+    }
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct TransactionStatusResult {
     pub ok: bool,
     pub status: Option<String>,
-    pub error: Option<String>,
+    pub error: Option<NetworkError>,
     pub network_error: bool,
 }
 
+impl TransactionStatusResult {
+    fn failed(error: NetworkError) -> Self {
+        TransactionStatusResult {
+            ok: false,
+            status: None,
+            network_error: error.is_connectivity(),
+            error: Some(error),
+        }
+    }
+}
+
 fn str_from_value(v: &Value) -> Option<String> {
     v.as_str().map(String::from)
 }
@@ -235,13 +902,38 @@ fn amount_flat_from_value(v: &Value) -> Option<String> {
         .or_else(|| v.as_u64().map(|n| n.to_string()))
         .or_else(|| v.as_i64().map(|n| n.to_string()))
         .or_else(|| {
-            v.as_f64().map(|f| {
-                let flat = (f * 1_000_000_000.0).round() as i64;
-                flat.to_string()
-            })
+            // Fractional numbers are whole-coin amounts; parse their decimal text so nothing is rounded.
+            v.as_number()
+                .and_then(|n| plain_decimal(&n.to_string()))
+                .and_then(|n| parse_amount(&n).ok())
+                .map(|flat| flat.to_string())
         })
 }
 
+/// serde_json prints very small and very large numbers in exponent form (`1e-7`, `1.5e21`), which
+/// `parse_amount` rejects; this shifts the decimal point instead so the digits stay exact.
+fn plain_decimal(number: &str) -> Option<String> {
+    let Some((mantissa, exponent)) = number.split_once(['e', 'E']) else {
+        return Some(number.to_string());
+    };
+    // Anything further out has more digits than an amount can hold.
+    let exponent: i32 = exponent.parse().ok().filter(|e: &i32| e.abs() <= 64)?;
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i32 + exponent;
+    Some(if point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    })
+}
+
+fn display_amount(amount_flat: &str) -> Option<String> {
+    amount_flat.parse::<u128>().ok().map(format_amount)
+}
+
 fn action_args_get(item: &Value, idx: usize) -> Option<&Value> {
     item.get("tx")
         .and_then(|t| t.get("action"))
@@ -333,6 +1025,7 @@ fn parse_tx_item(item: &Value, my_address: &str) -> Option<TransactionItem> {
         kind: kind.to_string(),
         from_address: from,
         to_address: to,
+        amount: display_amount(&amount_flat),
         amount_flat,
         block_height,
         status,
@@ -371,24 +1064,25 @@ fn parse_tx_list(data: &Value, my_address: &str) -> Vec<TransactionItem> {
 }
 
 async fn fetch_tx_list(
-    client: &reqwest::Client,
-    url: &str,
-) -> Result<(reqwest::StatusCode, String), String> {
-    let res = client.get(url).send().await.map_err(|e| e.to_string())?;
+    http: &HttpClient,
+    api_url: &str,
+    path: &str,
+) -> Result<(reqwest::StatusCode, String), NetworkError> {
+    let res = http.get_with_failover(api_url, path).await?;
     let status = res.status();
-    let text = res.text().await.map_err(|e| e.to_string())?;
+    let text = res.text().await?;
     Ok((status, text))
 }
 
-fn build_tx_filter_url(base: &str, signer: bool, addr: &str, cursor: Option<&str>) -> String {
+fn build_tx_filter_path(signer: bool, addr: &str, cursor: Option<&str>) -> String {
     let (param, value) = if signer {
         ("signer", addr)
     } else {
         ("arg0", addr)
     };
     let mut url = format!(
-        "{}/api/chain/tx_by_filter?{}={}&limit=50&sort=desc",
-        base, param, value
+        "/api/chain/tx_by_filter?{}={}&limit=50&sort=desc",
+        param, value
     );
     if let Some(c) = cursor {
         if !c.is_empty() {
@@ -400,54 +1094,28 @@ fn build_tx_filter_url(base: &str, signer: bool, addr: &str, cursor: Option<&str
 }
 
 pub async fn get_transactions_from_api(
+    http: &HttpClient,
     api_url: &str,
     address_base58: &str,
     sent_cursor: Option<&str>,
     received_cursor: Option<&str>,
-) -> Result<TransactionsResult, String> {
-    let base = api_url.trim_end_matches('/');
+) -> Result<TransactionsResult, NetworkError> {
     let addr = address_base58.trim();
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let url_sent = build_tx_filter_url(base, true, addr, sent_cursor);
-    let url_received = build_tx_filter_url(base, false, addr, received_cursor);
+    let path_sent = build_tx_filter_path(true, addr, sent_cursor);
+    let path_received = build_tx_filter_path(false, addr, received_cursor);
 
-    let (sent_status, sent_text) = match fetch_tx_list(&client, &url_sent).await {
+    let (sent_status, sent_text) = match fetch_tx_list(http, api_url, &path_sent).await {
         Ok(s) => s,
-        Err(e) => {
-            let network_error = e.contains("connection") || e.contains("dns") || e.contains("timed out");
-            return Ok(TransactionsResult {
-                ok: false,
-                transactions: vec![],
-                next_sent_cursor: None,
-                next_received_cursor: None,
-                error: Some(e),
-                network_error,
-            });
-        }
+        Err(e) => return Ok(TransactionsResult::failed(e)),
     };
 
     if !sent_status.is_success() {
-        let code = sent_status.as_u16();
-        let err_msg = if code == 404 {
-            "Transaction history not supported by this node".to_string()
-        } else if sent_text.trim().is_empty() {
-            format!("HTTP {}", code)
-        } else {
-            format!("HTTP {}: {}", code, sent_text.trim())
+        let error = match sent_status.as_u16() {
+            404 => NetworkError::NotFound("Transaction history not supported by this node".to_string()),
+            code => NetworkError::from_status(code, &sent_text),
         };
-        return Ok(TransactionsResult {
-            ok: false,
-            transactions: vec![],
-            next_sent_cursor: None,
-            next_received_cursor: None,
-            error: Some(err_msg),
-            network_error: false,
-        });
+        return Ok(TransactionsResult::failed(error));
     }
 
     let (mut transactions, next_sent_cursor) = if let Ok(data) = serde_json::from_str::<Value>(&sent_text) {
@@ -458,7 +1126,7 @@ pub async fn get_transactions_from_api(
         (vec![], None)
     };
 
-    let (received, next_received_cursor) = match fetch_tx_list(&client, &url_received).await {
+    let (received, next_received_cursor) = match fetch_tx_list(http, api_url, &path_received).await {
         Ok((status, text)) if status.is_success() => {
             if let Ok(data) = serde_json::from_str::<Value>(&text) {
                 let list = parse_tx_list(&data, addr);
@@ -528,64 +1196,38 @@ pub async fn get_transactions_from_api(
 }
 
 pub async fn get_transaction_status_from_api(
+    http: &HttpClient,
     api_url: &str,
     tx_hash: &str,
-) -> Result<TransactionStatusResult, String> {
-    let base = api_url.trim_end_matches('/');
+) -> Result<TransactionStatusResult, NetworkError> {
     let hash = tx_hash.trim();
     if hash.is_empty() {
-        return Ok(TransactionStatusResult {
-            ok: false,
-            status: None,
-            error: Some("Empty transaction hash".to_string()),
-            network_error: false,
-        });
+        return Ok(TransactionStatusResult::failed(NetworkError::InvalidTransaction(
+            "Empty transaction hash".to_string(),
+        )));
     }
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let urls_to_try = [
-        format!("{}/api/chain/tx/{}", base, hash),
-        format!("{}/api/tx/{}", base, hash),
-        format!("{}/api/chain/tx?hash={}", base, hash),
+    let paths_to_try = [
+        format!("/api/chain/tx/{}", hash),
+        format!("/api/tx/{}", hash),
+        format!("/api/chain/tx?hash={}", hash),
     ];
 
-    for url in &urls_to_try {
-        let res = match client.get(url).send().await {
+    for path in &paths_to_try {
+        let res = match http.get_with_failover(api_url, path).await {
             Ok(r) => r,
-            Err(e) => {
-                let msg = e.to_string();
-                let network_error = msg.contains("connection") || msg.contains("dns") || msg.contains("timed out");
-                return Ok(TransactionStatusResult {
-                    ok: false,
-                    status: None,
-                    error: Some(msg),
-                    network_error,
-                });
-            }
+            Err(e) => return Ok(TransactionStatusResult::failed(e.into())),
         };
 
         let status = res.status();
         let code = status.as_u16();
-        let text = res.text().await.map_err(|e| e.to_string())?;
+        let text = res.text().await?;
 
         if !status.is_success() {
             if code == 404 {
                 continue; // try next URL
             }
-            return Ok(TransactionStatusResult {
-                ok: false,
-                status: None,
-                error: Some(if text.trim().is_empty() {
-                    format!("HTTP {}", code)
-                } else {
-                    format!("HTTP {}: {}", code, text.trim())
-                }),
-                network_error: false,
-            });
+            return Ok(TransactionStatusResult::failed(NetworkError::from_status(code, &text)));
         }
 
         if text.trim().is_empty() {
@@ -616,12 +1258,100 @@ pub async fn get_transaction_status_from_api(
         }
     }
 
-    Ok(TransactionStatusResult {
-        ok: false,
-        status: None,
-        error: Some("Transaction not found or status not available".to_string()),
-        network_error: false,
-    })
+    Ok(TransactionStatusResult::failed(NetworkError::NotFound(
+        "Transaction not found or status not available".to_string(),
+    )))
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct NonceResult {
+    pub ok: bool,
+    /// Decimal string; `None` when the account has never sent a transaction.
+    pub nonce: Option<String>,
+    pub error: Option<NetworkError>,
+    pub network_error: bool,
+}
+
+impl NonceResult {
+    fn failed(error: NetworkError) -> Self {
+        NonceResult {
+            ok: false,
+            nonce: None,
+            network_error: error.is_connectivity(),
+            error: Some(error),
+        }
+    }
+}
+
+fn nonce_from_value(v: &Value) -> Option<i128> {
+    v.as_i64()
+        .map(i128::from)
+        .or_else(|| v.as_u64().map(i128::from))
+        .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+/// The chain's last nonce for an account: from the nonce endpoint if the node has one, otherwise the
+/// highest nonce among the newest transactions the account signed.
+pub async fn get_account_nonce_from_api(
+    http: &HttpClient,
+    api_url: &str,
+    address_base58: &str,
+) -> Result<NonceResult, NetworkError> {
+    let addr = address_base58.trim();
+
+    let paths_to_try = [
+        format!("/api/chain/nonce/{}", addr),
+        build_tx_filter_path(true, addr, None),
+    ];
+
+    for path in &paths_to_try {
+        let res = match http.get_with_failover(api_url, path).await {
+            Ok(r) => r,
+            Err(e) => return Ok(NonceResult::failed(e.into())),
+        };
+
+        let status = res.status();
+        let code = status.as_u16();
+        let text = res.text().await?;
+
+        if !status.is_success() {
+            if code == 404 {
+                continue; // try next URL
+            }
+            return Ok(NonceResult::failed(NetworkError::from_status(code, &text)));
+        }
+
+        let data: Value = match serde_json::from_str(&text) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+
+        if let Some(nonce) = data.get("nonce").and_then(nonce_from_value) {
+            return Ok(NonceResult {
+                ok: true,
+                nonce: Some(nonce.to_string()),
+                error: None,
+                network_error: false,
+            });
+        }
+        if let Some(txs) = data.get("txs").or_else(|| data.get("transactions")).and_then(Value::as_array) {
+            let nonce = txs
+                .iter()
+                .filter_map(|t| t.get("tx").and_then(|tx| tx.get("nonce")).or_else(|| t.get("nonce")))
+                .filter_map(nonce_from_value)
+                .max();
+            return Ok(NonceResult {
+                ok: true,
+                nonce: nonce.map(|n| n.to_string()),
+                error: None,
+                network_error: false,
+            });
+        }
+    }
+
+    Ok(NonceResult::failed(NetworkError::NotFound(
+        "Node did not report a nonce".to_string(),
+    )))
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -629,23 +1359,48 @@ pub struct HealthCheckResult {
     pub ok: bool,
     pub status_code: Option<u16>,
     pub message: String,
+    pub error: Option<NetworkError>,
 }
 
-pub async fn check_node_health(api_url: &str) -> HealthCheckResult {
-    let base = api_url.trim_end_matches('/');
-    let client = match reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            return HealthCheckResult {
-                ok: false,
-                status_code: None,
-                message: format!("Failed to create HTTP client: {}", e),
-            };
+impl HealthCheckResult {
+    fn failed(status_code: Option<u16>, error: NetworkError) -> Self {
+        HealthCheckResult {
+            ok: false,
+            status_code,
+            message: error.to_string(),
+            error: Some(error),
         }
+    }
+}
+
+/// Checks `api_url` itself (no failover) and records the outcome in the node pool.
+pub async fn check_node_health(http: &HttpClient, api_url: &str) -> HealthCheckResult {
+    // Not the node's fault, so nothing is recorded against it.
+    let client = match http.client() {
+        Ok(client) => client,
+        Err(error) => return HealthCheckResult::failed(None, error),
     };
+    let base = normalize_url(api_url);
+    let started = Instant::now();
+    let result = probe_node(http, &client, &base).await;
+    if result.ok {
+        http.nodes.record_success(&base, started.elapsed());
+    } else {
+        http.nodes.record_failure(&base, result.message.clone());
+    }
+    result
+}
+
+/// Health-checks every node `api_url` can fail over to and returns the updated pool.
+pub async fn check_node_pool(http: &HttpClient, api_url: &str) -> NodePoolStatus {
+    for (url, _) in http.candidates(api_url) {
+        check_node_health(http, &url).await;
+    }
+    http.node_status(api_url)
+}
+
+async fn probe_node(http: &HttpClient, client: &reqwest::Client, base: &str) -> HealthCheckResult {
+    let timeout = Duration::from_secs(http.settings().health_timeout_secs);
 
     let urls_to_try: &[(String, &str)] = &[
         (format!("{}/api/chain/stats", base), "chain/stats"),
@@ -654,7 +1409,7 @@ pub async fn check_node_health(api_url: &str) -> HealthCheckResult {
     ];
 
     for (i, (url, label)) in urls_to_try.iter().enumerate() {
-        match client.get(url).send().await {
+        match client.get(url).timeout(timeout).send().await {
             Ok(res) => {
                 let status = res.status();
                 let code = status.as_u16();
@@ -663,42 +1418,111 @@ pub async fn check_node_health(api_url: &str) -> HealthCheckResult {
                         ok: true,
                         status_code: Some(code),
                         message: format!("OK (HTTP {}, {})", code, label),
+                        error: None,
                     };
                 }
                 if code == 404 || code == 405 {
                     continue;
                 }
-                return HealthCheckResult {
-                    ok: false,
-                    status_code: Some(code),
-                    message: format!(
-                        "HTTP {} — {}",
-                        code,
-                        if code == 520 {
-                            "Cloudflare: origin server error (node may be down)"
-                        } else if (500..600).contains(&code) {
-                            "server error"
-                        } else {
-                            "unexpected response"
-                        }
-                    ),
-                };
+                return HealthCheckResult::failed(Some(code), NetworkError::from_status(code, ""));
             }
             Err(e) => {
                 if i == urls_to_try.len() - 1 {
-                    return HealthCheckResult {
-                        ok: false,
-                        status_code: None,
-                        message: e.to_string(),
-                    };
+                    return HealthCheckResult::failed(None, e.into());
                 }
             }
         }
     }
 
-    HealthCheckResult {
-        ok: false,
-        status_code: None,
-        message: "No successful response from node".to_string(),
+    HealthCheckResult::failed(
+        None,
+        NetworkError::NotFound("No successful response from node".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_amounts_in_every_json_form() {
+        assert_eq!(amount_flat_from_value(&json!("1500000000")).as_deref(), Some("1500000000"));
+        assert_eq!(amount_flat_from_value(&json!(42)).as_deref(), Some("42"));
+        assert_eq!(amount_flat_from_value(&json!(0.5)).as_deref(), Some("500000000"));
+        assert_eq!(amount_flat_from_value(&json!(1e-7)).as_deref(), Some("100"));
+        assert_eq!(amount_flat_from_value(&json!(1.5e-9)), None);
+        assert_eq!(
+            amount_flat_from_value(&json!(1e21)).as_deref(),
+            Some("1000000000000000000000000000000")
+        );
+    }
+
+    #[test]
+    fn expands_exponents_exactly() {
+        assert_eq!(plain_decimal("12.5").as_deref(), Some("12.5"));
+        assert_eq!(plain_decimal("1e-7").as_deref(), Some("0.0000001"));
+        assert_eq!(plain_decimal("1.25e-3").as_deref(), Some("0.00125"));
+        assert_eq!(plain_decimal("1.25e1").as_deref(), Some("12.5"));
+        assert_eq!(plain_decimal("1.5e21").as_deref(), Some("1500000000000000000000"));
+        assert_eq!(plain_decimal("1e+3").as_deref(), Some("1000"));
+        assert_eq!(plain_decimal("1e999"), None);
+    }
+
+    #[test]
+    fn codes_and_retry_classes() {
+        let cases: Vec<(NetworkError, &str, bool, bool)> = vec![
+            // (error, code, is_transient, is_connectivity)
+            (NetworkError::Timeout, "timeout", true, true),
+            (NetworkError::Dns("x".into()), "dns", true, true),
+            (NetworkError::Connect("x".into()), "connect", true, true),
+            (NetworkError::Tls("x".into()), "tls", false, true),
+            (NetworkError::Interrupted("x".into()), "interrupted", true, false),
+            (NetworkError::Redirect("x".into()), "redirect", false, false),
+            (NetworkError::Cloudflare { status: 522 }, "cloudflare", true, false),
+            (NetworkError::Decode("x".into()), "decode", false, false),
+            (NetworkError::NotFound("x".into()), "not_found", false, false),
+            (NetworkError::InvalidUrl("x".into()), "invalid_url", false, false),
+            (NetworkError::InvalidTransaction("x".into()), "invalid_transaction", false, false),
+            (NetworkError::Settings("x".into()), "settings", false, false),
+        ];
+        for (e, code, transient, connectivity) in cases {
+            assert_eq!(e.code(), code);
+            assert_eq!(e.is_transient(), transient, "{}", code);
+            assert_eq!(e.is_connectivity(), connectivity, "{}", code);
+        }
+    }
+
+    #[test]
+    fn only_some_http_statuses_are_transient() {
+        let statuses = [(400, false), (403, false), (408, true), (429, true), (500, true), (503, true)];
+        for (status, transient) in statuses {
+            let e = NetworkError::from_status(status, "");
+            assert_eq!(e.code(), "http_status");
+            assert_eq!(e.is_transient(), transient, "HTTP {}", status);
+            assert!(!e.is_connectivity());
+        }
+        assert_eq!(NetworkError::from_status(404, "").code(), "not_found");
+        assert_eq!(NetworkError::from_status(524, "").code(), "cloudflare");
+        assert_eq!(
+            NetworkError::from_status(503, " busy ").details(),
+            Some(json!({ "status": 503, "body": "busy" }))
+        );
+    }
+
+    fn request_error(url: &str) -> NetworkError {
+        let client = HttpSettings::default().build_client().unwrap();
+        tauri::async_runtime::block_on(client.get(url).send()).unwrap_err().into()
+    }
+
+    #[test]
+    fn classifies_a_failed_lookup_as_dns() {
+        // `.invalid` never resolves (RFC 6761).
+        assert_eq!(request_error("http://amadeus-wallet-test.invalid/").code(), "dns");
+    }
+
+    #[test]
+    fn classifies_a_refused_connection_as_connect() {
+        assert_eq!(request_error("http://127.0.0.1:1/").code(), "connect");
     }
 }
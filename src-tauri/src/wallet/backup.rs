@@ -0,0 +1,396 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::keystore::{self, Account, Keystore};
+use crate::wallet::storage::{self, KdfPreset, Sealed};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+const BACKUP_FORMAT: &str = "amadeus-wallet-backup";
+const BACKUP_VERSION: u32 = 1;
+
+/// The bundle on disk. Only `format` and `version` are readable without the backup password, and
+/// both are authenticated by the cipher so they cannot be altered.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct BackupFile {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BackupContents {
+    metadata: BackupMetadata,
+    selected: Option<String>,
+    accounts: Vec<BackupAccount>,
+    #[serde(default)]
+    settings: BackupSettings,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BackupAccount {
+    #[serde(flatten)]
+    account: Account,
+    /// The account's key file exactly as stored, so it still needs the account's own password.
+    key_file: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BackupMetadata {
+    pub app_version: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub account_count: usize,
+}
+
+/// State the frontend keeps outside the wallet directory, carried along in the backup.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct BackupSettings {
+    #[serde(default)]
+    pub address_book: Vec<AddressBookEntry>,
+    #[serde(default)]
+    pub network: NetworkSettings,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AddressBookEntry {
+    pub address: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct NetworkSettings {
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RestoredBackup {
+    pub metadata: BackupMetadata,
+    pub added: Vec<Account>,
+    /// Labels of backed-up accounts that were already in this wallet.
+    pub skipped: Vec<String>,
+    /// For the frontend to apply; nothing here is written by the backend.
+    pub settings: BackupSettings,
+}
+
+/// Writes every account in `keystore` plus `settings` to a new file at `path`, encrypted under
+/// `password`. Refuses to overwrite an existing file.
+pub fn export(
+    keystore: &Keystore,
+    password: &str,
+    settings: BackupSettings,
+    path: &Path,
+) -> Result<BackupMetadata, WalletError> {
+    if path.exists() {
+        return Err(WalletError::Storage(format!("{} already exists", path.display())));
+    }
+    let list = keystore.list();
+    let accounts = list
+        .accounts
+        .into_iter()
+        .map(|account| {
+            let key_file = std::fs::read_to_string(keystore.key_path(&account.id))
+                .map_err(|e| WalletError::Storage(format!("Reading key of \"{}\": {}", account.label, e)))?;
+            Ok(BackupAccount { account, key_file })
+        })
+        .collect::<Result<Vec<_>, WalletError>>()?;
+    let metadata = BackupMetadata {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        account_count: accounts.len(),
+    };
+    let contents = BackupContents {
+        metadata: metadata.clone(),
+        selected: list.selected,
+        accounts,
+        settings,
+    };
+    let plaintext = Zeroizing::new(serde_json::to_vec(&contents).map_err(|e| WalletError::Storage(e.to_string()))?);
+    // Backups travel, so they get a stronger KDF than a key file on this machine.
+    let file = BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        sealed: storage::seal(&plaintext, password, KdfPreset::Moderate.cost(), &aad(BACKUP_VERSION))?,
+    };
+    let data = serde_json::to_vec_pretty(&file).map_err(|e| WalletError::Storage(e.to_string()))?;
+    storage::write_atomic(path, &data)?;
+    Ok(metadata)
+}
+
+/// Adds the accounts in a backup that are not already in `keystore`. Everything is decrypted and
+/// checked before the first key file is written. If the wallet was empty, the backup's selected
+/// account is selected. On failure nothing is saved and `keystore` should be discarded.
+pub fn import(keystore: &mut Keystore, password: &str, path: &Path) -> Result<RestoredBackup, WalletError> {
+    let data = std::fs::read(path).map_err(|e| WalletError::Storage(e.to_string()))?;
+    let file: BackupFile = serde_json::from_slice(&data).map_err(|e| WalletError::CorruptFile(e.to_string()))?;
+    if file.format != BACKUP_FORMAT {
+        return Err(WalletError::CorruptFile("Not a wallet backup".into()));
+    }
+    if file.version > BACKUP_VERSION {
+        return Err(WalletError::CorruptFile(format!(
+            "Backup version {} is newer than this app supports",
+            file.version
+        )));
+    }
+    let plaintext = storage::open(&file.sealed, password, &aad(file.version))?;
+    let contents: BackupContents =
+        serde_json::from_slice(&plaintext).map_err(|e| WalletError::CorruptFile(e.to_string()))?;
+    if contents.accounts.len() != contents.metadata.account_count {
+        return Err(WalletError::CorruptFile("Backup is missing accounts".into()));
+    }
+    for entry in &contents.accounts {
+        let account = &entry.account;
+        let root = account.derivation.as_ref().map(|d| d.root.as_str());
+        if !keystore::is_account_id(&account.id) || root.is_some_and(|r| !keystore::is_account_id(r)) {
+            return Err(WalletError::CorruptFile(format!(
+                "Account \"{}\" has an invalid id",
+                account.label
+            )));
+        }
+        let recorded = storage::inspect_key_file(entry.key_file.as_bytes()).map_err(|e| {
+            WalletError::CorruptFile(format!("Key of \"{}\": {}", entry.account.label, e))
+        })?;
+        if recorded.is_some() && entry.account.public_key.is_some() && recorded != entry.account.public_key {
+            return Err(WalletError::CorruptFile(format!(
+                "Key of \"{}\" does not match its public key",
+                entry.account.label
+            )));
+        }
+    }
+
+    // Key files go in before the index that lists them. If a write or the final save fails, the
+    // files written so far are removed again so no key is left behind without an index entry.
+    let mut written = Vec::new();
+    let restored = restore_accounts(keystore, contents.accounts, contents.selected, &mut written);
+    let (added, skipped) = match restored.and_then(|restored| keystore.save().map(|()| restored)) {
+        Ok(restored) => restored,
+        Err(e) => {
+            for path in &written {
+                let _ = std::fs::remove_file(path);
+            }
+            return Err(e);
+        }
+    };
+    Ok(RestoredBackup {
+        metadata: contents.metadata,
+        added,
+        skipped,
+        settings: contents.settings,
+    })
+}
+
+/// Writes the key files of the accounts not already in `keystore` and adds them to it, recording
+/// each file in `written`. Returns the accounts added and the labels of those skipped.
+fn restore_accounts(
+    keystore: &mut Keystore,
+    accounts: Vec<BackupAccount>,
+    selected: Option<String>,
+    written: &mut Vec<PathBuf>,
+) -> Result<(Vec<Account>, Vec<String>), WalletError> {
+    let was_empty = keystore.is_empty();
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in accounts {
+        let account = entry.account;
+        let present = keystore.get(&account.id).is_ok()
+            || account
+                .public_key
+                .as_deref()
+                .is_some_and(|pk| keystore.find_by_public_key(pk).is_some());
+        if present {
+            skipped.push(account.label);
+            continue;
+        }
+        let path = keystore.key_path(&account.id);
+        if path.exists() {
+            return Err(WalletError::Storage(format!("{} already exists", path.display())));
+        }
+        storage::write_atomic(&path, entry.key_file.as_bytes())?;
+        written.push(path);
+        keystore.restore(account.clone());
+        added.push(account);
+    }
+    if was_empty {
+        let selected = selected
+            .filter(|id| keystore.get(id).is_ok())
+            .or_else(|| added.first().map(|a| a.id.clone()));
+        if let Some(id) = selected {
+            keystore.select(&id)?;
+        }
+    }
+    Ok((added, skipped))
+}
+
+fn aad(version: u32) -> Vec<u8> {
+    format!("{}/{}", BACKUP_FORMAT, version).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::keys;
+    use crate::wallet::storage::KdfCost;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use serde_json::json;
+
+    const PASSWORD: &str = "backup password";
+    const KEY_PASSWORD: &str = "key password";
+    const CHEAP: KdfCost = KdfCost {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("amadeus-backup-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A keystore in a fresh directory holding `count` accounts.
+    fn keystore_with_accounts(count: usize) -> Keystore {
+        let mut keystore = Keystore::load(&temp_dir()).unwrap();
+        for _ in 0..count {
+            let sk = keys::generate_private_key_base58().unwrap();
+            let account = keystore.add(None, keys::public_key_from_private_base58(sk.expose()).unwrap());
+            storage::encrypt_and_store(&sk, KEY_PASSWORD, &keystore.key_path(&account.id), CHEAP).unwrap();
+        }
+        keystore.save().unwrap();
+        keystore
+    }
+
+    /// Seals `contents` the way `export` does, but cheaply and without checking what is in it.
+    fn write_backup(contents: &BackupContents, path: &Path) {
+        let file = BackupFile {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            sealed: storage::seal(&serde_json::to_vec(contents).unwrap(), PASSWORD, CHEAP, &aad(BACKUP_VERSION))
+                .unwrap(),
+        };
+        std::fs::write(path, serde_json::to_vec(&file).unwrap()).unwrap();
+    }
+
+    fn contents_of(keystore: &Keystore) -> BackupContents {
+        let list = keystore.list();
+        let accounts: Vec<BackupAccount> = list
+            .accounts
+            .into_iter()
+            .map(|account| BackupAccount {
+                key_file: std::fs::read_to_string(keystore.key_path(&account.id)).unwrap(),
+                account,
+            })
+            .collect();
+        BackupContents {
+            metadata: BackupMetadata {
+                app_version: "test".to_string(),
+                created_at: 0,
+                account_count: accounts.len(),
+            },
+            selected: list.selected,
+            accounts,
+            settings: BackupSettings::default(),
+        }
+    }
+
+    fn edit_file(path: &Path, pointer: &str, value: serde_json::Value) {
+        let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        *json.pointer_mut(pointer).unwrap() = value;
+        std::fs::write(path, serde_json::to_vec(&json).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn round_trips_accounts_and_settings() {
+        let source = keystore_with_accounts(2);
+        let path = temp_dir().join("wallet.backup");
+        let settings = BackupSettings {
+            address_book: vec![AddressBookEntry {
+                address: "addr".to_string(),
+                label: Some("friend".to_string()),
+            }],
+            network: NetworkSettings {
+                api_url: Some("https://node.example".to_string()),
+            },
+        };
+        export(&source, PASSWORD, settings, &path).unwrap();
+        assert!(export(&source, PASSWORD, BackupSettings::default(), &path).is_err());
+
+        let mut target = Keystore::load(&temp_dir()).unwrap();
+        let restored = import(&mut target, PASSWORD, &path).unwrap();
+        assert_eq!(restored.added.len(), 2);
+        assert!(restored.skipped.is_empty());
+        assert_eq!(restored.settings.address_book[0].label.as_deref(), Some("friend"));
+        assert_eq!(target.selected().unwrap().id, source.selected().unwrap().id);
+        for account in source.list().accounts {
+            let original = storage::load_and_decrypt(KEY_PASSWORD, &source.key_path(&account.id)).unwrap();
+            let copy = storage::load_and_decrypt(KEY_PASSWORD, &target.key_path(&account.id)).unwrap();
+            assert_eq!(copy.expose(), original.expose());
+        }
+
+        let again = import(&mut target, PASSWORD, &path).unwrap();
+        assert!(again.added.is_empty());
+        assert_eq!(again.skipped.len(), 2);
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let path = temp_dir().join("wallet.backup");
+        write_backup(&contents_of(&keystore_with_accounts(1)), &path);
+        let mut target = Keystore::load(&temp_dir()).unwrap();
+        assert!(matches!(import(&mut target, "wrong", &path), Err(WalletError::DecryptionFailed)));
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn rejects_a_tampered_header_or_ciphertext() {
+        let path = temp_dir().join("wallet.backup");
+        write_backup(&contents_of(&keystore_with_accounts(1)), &path);
+        let original = std::fs::read(&path).unwrap();
+        let mut target = Keystore::load(&temp_dir()).unwrap();
+
+        edit_file(&path, "/version", json!(0));
+        assert!(matches!(import(&mut target, PASSWORD, &path), Err(WalletError::DecryptionFailed)));
+
+        std::fs::write(&path, &original).unwrap();
+        let file: serde_json::Value = serde_json::from_slice(&original).unwrap();
+        let mut ciphertext = BASE64.decode(file["ciphertext"].as_str().unwrap()).unwrap();
+        ciphertext[0] ^= 1;
+        edit_file(&path, "/ciphertext", json!(BASE64.encode(ciphertext)));
+        assert!(matches!(import(&mut target, PASSWORD, &path), Err(WalletError::DecryptionFailed)));
+        assert!(target.is_empty());
+
+        std::fs::write(&path, &original).unwrap();
+        assert!(import(&mut target, PASSWORD, &path).is_ok());
+    }
+
+    #[test]
+    fn rejects_ids_that_could_escape_the_wallet_directory() {
+        let mut contents = contents_of(&keystore_with_accounts(1));
+        contents.accounts[0].account.id = "../x".to_string();
+        let path = temp_dir().join("wallet.backup");
+        write_backup(&contents, &path);
+        let dir = temp_dir();
+        let mut target = Keystore::load(&dir).unwrap();
+        assert!(matches!(import(&mut target, PASSWORD, &path), Err(WalletError::CorruptFile(_))));
+        assert!(!dir.join("x.enc").exists());
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn removes_written_key_files_when_the_import_fails() {
+        let source = keystore_with_accounts(2);
+        let path = temp_dir().join("wallet.backup");
+        write_backup(&contents_of(&source), &path);
+        let ids: Vec<String> = source.list().accounts.into_iter().map(|a| a.id).collect();
+        let dir = temp_dir();
+        let mut target = Keystore::load(&dir).unwrap();
+        // A stray file where the second key goes makes the import fail after the first is written.
+        storage::write_atomic(&target.key_path(&ids[1]), b"stray").unwrap();
+        assert!(import(&mut target, PASSWORD, &path).is_err());
+        assert!(!target.key_path(&ids[0]).exists());
+        assert!(Keystore::load(&dir).unwrap().is_empty());
+    }
+}
@@ -11,9 +11,111 @@ pub enum WalletError {
     #[error("Invalid private key: {0}")]
     InvalidPrivateKey(String),
 
+    #[error("A wallet for this key already exists (\"{0}\")")]
+    WalletExists(String),
+
+    #[error("Account not found: {0}")]
+    AccountNotFound(String),
+
+    #[error("No wallet found. Create or import one first.")]
+    NoWallet,
+
+    #[error("Invalid recovery phrase: {0}")]
+    InvalidMnemonic(String),
+
+    #[error("Invalid key share: {0}")]
+    InvalidShare(String),
+
+    #[error("Network error: {0}")]
+    Network(#[from] crate::network::NetworkError),
+
+    #[error("Wallet is locked. Unlock it or provide the password.")]
+    Locked,
+
+    #[error("Invalid setting: {0}")]
+    InvalidSetting(String),
+
     #[error("Decryption failed (wrong password?)")]
     DecryptionFailed,
 
+    #[error("Wallet file is corrupt or unsupported: {0}")]
+    CorruptFile(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Invalid nonce: {0}")]
+    InvalidNonce(String),
+
+    #[error("Invalid Base58: {0}")]
+    InvalidBase58(String),
+
+    #[error("Malformed vecpak: {0}")]
+    Vecpak(#[from] crate::amadeus_tx::VecpakError),
+
+    #[error("Signature verification failed")]
+    InvalidSignature,
+
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(#[from] crate::wallet::keys::PublicKeyError),
+
+    #[error("Invalid recipient: {0}")]
+    InvalidRecipient(crate::wallet::keys::PublicKeyError),
+
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+
+    #[error("Invalid multisig transaction: {0}")]
+    InvalidMultisig(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+impl WalletError {
+    /// Stable identifier for the frontend; never reword these. Network errors use the
+    /// `NetworkError` codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            WalletError::Crypto(_) => "crypto",
+            WalletError::Storage(_) => "storage",
+            WalletError::InvalidPrivateKey(_) => "invalid_private_key",
+            WalletError::WalletExists(_) => "wallet_exists",
+            WalletError::AccountNotFound(_) => "account_not_found",
+            WalletError::NoWallet => "no_wallet",
+            WalletError::InvalidMnemonic(_) => "invalid_mnemonic",
+            WalletError::InvalidShare(_) => "invalid_share",
+            WalletError::Network(e) => e.code(),
+            WalletError::Locked => "locked",
+            WalletError::InvalidSetting(_) => "invalid_setting",
+            WalletError::DecryptionFailed => "wrong_password",
+            WalletError::CorruptFile(_) => "corrupt_file",
+            WalletError::InvalidAmount(_) => "invalid_amount",
+            WalletError::InvalidTransaction(_) => "invalid_transaction",
+            WalletError::InvalidNonce(_) => "invalid_nonce",
+            WalletError::InvalidBase58(_) => "invalid_base58",
+            WalletError::Vecpak(_) => "malformed_vecpak",
+            WalletError::InvalidSignature => "invalid_signature",
+            WalletError::InvalidPublicKey(_) => "invalid_public_key",
+            WalletError::InvalidRecipient(_) => "invalid_recipient",
+            WalletError::InvalidMessage(_) => "invalid_message",
+            WalletError::InvalidMultisig(_) => "invalid_multisig",
+            WalletError::Io(_) => "io",
+        }
+    }
+
+    /// Structured data for the codes whose message has a variable part the UI may want to show on
+    /// its own (e.g. the label of the wallet that already exists).
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            WalletError::WalletExists(label) => Some(serde_json::json!({ "label": label })),
+            WalletError::AccountNotFound(id) => Some(serde_json::json!({ "id": id })),
+            WalletError::InvalidPublicKey(e) | WalletError::InvalidRecipient(e) => serde_json::to_value(e).ok(),
+            WalletError::Network(e) => e.details(),
+            _ => None,
+        }
+    }
+}
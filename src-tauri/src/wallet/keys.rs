@@ -1,81 +1,283 @@
 use crate::wallet::error::WalletError;
+use crate::wallet::secret::{PrivateKey, SecretString, PRIVATE_KEY_LEN};
 use bls12_381::Scalar;
-use blst::min_pk::SecretKey as BlsSecretKey;
+use blst::min_pk::{
+    AggregatePublicKey, AggregateSignature, PublicKey as BlsPublicKey, SecretKey as BlsSecretKey,
+    Signature as BlsSignature,
+};
+use blst::BLST_ERROR;
 use group::Curve;
+use hkdf::Hkdf;
 use rand::RngCore;
 use bs58;
+use sha2::Sha512;
+use zeroize::Zeroizing;
 
-const PRIVATE_KEY_LEN: usize = 64;
 const PUBLIC_KEY_LEN: usize = 48;
+/// HKDF salt for child keys; bump the version if the derivation ever changes.
+const HD_SALT: &[u8] = b"AMADEUS_WALLET_HD_V1";
 
-pub fn generate_private_key_base58() -> Result<String, WalletError> {
-    let mut sk_64 = [0u8; PRIVATE_KEY_LEN];
+pub fn generate_private_key_base58() -> Result<SecretString, WalletError> {
+    let mut sk_64 = Zeroizing::new([0u8; PRIVATE_KEY_LEN]);
     loop {
-        rand::rng().fill_bytes(&mut sk_64);
-        let scalar = Scalar::from_bytes_wide(&sk_64);
-        let mut sk_be = scalar.to_bytes();
-        sk_be.reverse();
-        if BlsSecretKey::from_bytes(&sk_be).is_ok() {
-            return Ok(bs58::encode(sk_64).into_string());
+        rand::rng().fill_bytes(&mut *sk_64);
+        if bls_secret_key(&sk_64).is_ok() {
+            return Ok(SecretString::new(bs58::encode(&*sk_64).into_string()));
         }
     }
 }
 
 pub fn public_key_from_private_base58(private_key_base58: &str) -> Result<String, WalletError> {
-    use bls12_381::G1Projective;
-    let sk_bytes = decode_private_key_base58(private_key_base58)?;
-    let sk_scalar = Scalar::from_bytes_wide(&sk_bytes);
-    let pk_g1 = G1Projective::generator() * sk_scalar;
-    let pk_bytes = pk_g1.to_affine().to_compressed();
-    Ok(bs58::encode(pk_bytes).into_string())
+    let sk = decode_private_key_base58(private_key_base58)?;
+    Ok(bs58::encode(public_key_bytes(&sk)).into_string())
+}
+
+pub fn parse_private_key_base58(base58_str: &str) -> Result<SecretString, WalletError> {
+    Ok(encode_private_key_base58(&decode_private_key_base58(base58_str)?))
 }
 
-pub fn parse_private_key_base58(base58_str: &str) -> Result<String, WalletError> {
-    let decoded = decode_private_key_base58(base58_str)?;
-    Ok(bs58::encode(decoded).into_string())
+/// Why a string is not a usable public key (and so not an address).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, serde::Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum PublicKeyError {
+    #[error("not valid Base58")]
+    BadBase58,
+    #[error("must be {} bytes (Base58 decoded), got {len}", PUBLIC_KEY_LEN)]
+    WrongLength { len: usize },
+    #[error("not a valid BLS12-381 public key")]
+    InvalidPoint,
 }
 
-pub fn validate_public_key_base58(s: &str) -> Result<bool, WalletError> {
-    let decoded = bs58::decode(s.trim())
-        .into_vec()
-        .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
-    Ok(decoded.len() == PUBLIC_KEY_LEN)
+/// Result of `validate_public_key_base58`, for the frontend.
+#[derive(Debug, serde::Serialize)]
+pub struct PublicKeyValidation {
+    pub valid: bool,
+    pub error: Option<PublicKeyError>,
+    pub message: Option<String>,
+}
+
+pub fn validate_public_key_base58(s: &str) -> PublicKeyValidation {
+    match check_public_key_base58(s) {
+        Ok(_) => PublicKeyValidation {
+            valid: true,
+            error: None,
+            message: None,
+        },
+        Err(e) => PublicKeyValidation {
+            valid: false,
+            message: Some(e.to_string()),
+            error: Some(e),
+        },
+    }
 }
 
-pub fn public_key_bytes_from_private_base58(private_key_base58: &str) -> Result<[u8; PUBLIC_KEY_LEN], WalletError> {
+/// Accepts only the canonical compressed encoding of a G1 point in the prime-order subgroup,
+/// other than the identity. Anything else can never sign, so funds sent to it are lost.
+pub fn check_public_key_base58(s: &str) -> Result<[u8; PUBLIC_KEY_LEN], PublicKeyError> {
+    let decoded = bs58::decode(s.trim()).into_vec().map_err(|_| PublicKeyError::BadBase58)?;
+    let bytes: [u8; PUBLIC_KEY_LEN] = decoded
+        .try_into()
+        .map_err(|v: Vec<u8>| PublicKeyError::WrongLength { len: v.len() })?;
+    // `key_validate` rejects points off the curve, outside the subgroup and the identity; the
+    // round trip rejects non-canonical encodings of a valid point.
+    let pk = BlsPublicKey::key_validate(&bytes).map_err(|_| PublicKeyError::InvalidPoint)?;
+    if pk.compress() != bytes {
+        return Err(PublicKeyError::InvalidPoint);
+    }
+    Ok(bytes)
+}
+
+pub fn decode_public_key_base58(s: &str) -> Result<[u8; PUBLIC_KEY_LEN], WalletError> {
+    Ok(check_public_key_base58(s)?)
+}
+
+pub fn public_key_bytes(sk: &PrivateKey) -> [u8; PUBLIC_KEY_LEN] {
     use bls12_381::G1Projective;
-    let sk_bytes = decode_private_key_base58(private_key_base58)?;
-    let sk_scalar = Scalar::from_bytes_wide(&sk_bytes);
+    let sk_scalar = Scalar::from_bytes_wide(sk.expose());
     let pk_g1 = G1Projective::generator() * sk_scalar;
-    let pk_bytes = pk_g1.to_affine().to_compressed();
-    Ok(pk_bytes)
+    pk_g1.to_affine().to_compressed()
 }
 
-pub fn sign_hash_with_dst(sk_bytes: &[u8; PRIVATE_KEY_LEN], hash: &[u8; 32], dst: &[u8]) -> Result<[u8; 96], WalletError> {
-    let sk_scalar = Scalar::from_bytes_wide(sk_bytes);
-    let mut sk_be = sk_scalar.to_bytes();
-    sk_be.reverse();
-    let sk = BlsSecretKey::from_bytes(&sk_be).map_err(|e| WalletError::Crypto(format!("{:?}", e)))?;
-    let sig = sk.sign(hash, dst, &[]);
+/// Signs `msg` (a transaction hash, or a whole message) under the domain separation tag `dst`.
+pub fn sign_with_dst(sk: &PrivateKey, msg: &[u8], dst: &[u8]) -> Result<[u8; 96], WalletError> {
+    let sk = bls_secret_key(sk.expose()).map_err(|e| WalletError::Crypto(format!("{:?}", e)))?;
+    let sig = sk.sign(msg, dst, &[]);
     Ok(sig.to_bytes())
 }
 
-pub fn decode_private_key_base58(s: &str) -> Result<[u8; PRIVATE_KEY_LEN], WalletError> {
-    let decoded = bs58::decode(s.trim())
-        .into_vec()
-        .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
-    if decoded.len() != PRIVATE_KEY_LEN {
-        return Err(WalletError::InvalidPrivateKey(format!(
+pub fn verify_with_dst(
+    pk_bytes: &[u8; PUBLIC_KEY_LEN],
+    msg: &[u8],
+    signature: &[u8; 96],
+    dst: &[u8],
+) -> Result<(), WalletError> {
+    let pk = BlsPublicKey::from_bytes(pk_bytes).map_err(|_| WalletError::InvalidSignature)?;
+    let sig = BlsSignature::from_bytes(signature).map_err(|_| WalletError::InvalidSignature)?;
+    match sig.verify(true, msg, dst, &[], &pk, true) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        _ => Err(WalletError::InvalidSignature),
+    }
+}
+
+/// Sums public keys into the single key that verifies their aggregated signature over one message.
+/// Each key is checked to be a valid, non-identity G1 point. A plain sum is open to rogue-key
+/// attacks, so callers must first check each key's proof of possession.
+pub fn aggregate_public_keys(pks: &[[u8; PUBLIC_KEY_LEN]]) -> Result<[u8; PUBLIC_KEY_LEN], WalletError> {
+    let pks = pks
+        .iter()
+        .map(|pk| BlsPublicKey::key_validate(pk).map_err(|e| WalletError::Crypto(format!("{:?}", e))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&BlsPublicKey> = pks.iter().collect();
+    let aggregate =
+        AggregatePublicKey::aggregate(&refs, false).map_err(|e| WalletError::Crypto(format!("{:?}", e)))?;
+    Ok(aggregate.to_public_key().compress())
+}
+
+pub fn aggregate_signatures(sigs: &[[u8; 96]]) -> Result<[u8; 96], WalletError> {
+    let sigs = sigs
+        .iter()
+        .map(|sig| BlsSignature::from_bytes(sig).map_err(|_| WalletError::InvalidSignature))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&BlsSignature> = sigs.iter().collect();
+    let aggregate = AggregateSignature::aggregate(&refs, true).map_err(|_| WalletError::InvalidSignature)?;
+    Ok(aggregate.to_signature().to_bytes())
+}
+
+pub fn decode_private_key_base58(s: &str) -> Result<PrivateKey, WalletError> {
+    let decoded = Zeroizing::new(
+        bs58::decode(s.trim())
+            .into_vec()
+            .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?,
+    );
+    let sk = PrivateKey::from_slice(&decoded).ok_or_else(|| {
+        WalletError::InvalidPrivateKey(format!(
             "Private key must be {} bytes (Base58 decoded), got {}",
             PRIVATE_KEY_LEN,
             decoded.len()
-        )));
-    }
-    let mut arr = [0u8; PRIVATE_KEY_LEN];
-    arr.copy_from_slice(&decoded);
-    let scalar = Scalar::from_bytes_wide(&arr);
-    let mut sk_be = scalar.to_bytes();
+        ))
+    })?;
+    validate_private_key(&sk)?;
+    Ok(sk)
+}
+
+pub fn encode_private_key_base58(sk: &PrivateKey) -> SecretString {
+    SecretString::new(bs58::encode(sk.expose()).into_string())
+}
+
+/// Derives child account `index` from a root account's 64-byte key:
+///
+/// `child = HKDF-SHA512(ikm = root key, salt = "AMADEUS_WALLET_HD_V1", info = "m/<index>", L = 64)`
+///
+/// where `<index>` is the decimal index (`m/0`, `m/1`, ...). The output is used exactly like a
+/// random key, so one backup of the root restores every child.
+pub fn derive_child_key(root: &PrivateKey, index: u32) -> Result<PrivateKey, WalletError> {
+    let mut okm = Zeroizing::new([0u8; PRIVATE_KEY_LEN]);
+    Hkdf::<Sha512>::new(Some(HD_SALT), root.expose())
+        .expand(format!("m/{}", index).as_bytes(), &mut *okm)
+        .map_err(|e| WalletError::Crypto(e.to_string()))?;
+    let child = PrivateKey::from_slice(&*okm).ok_or_else(|| WalletError::Crypto("Bad key length".into()))?;
+    validate_private_key(&child)?;
+    Ok(child)
+}
+
+/// Rejects keys that reduce to an unusable scalar (zero).
+pub fn validate_private_key(sk: &PrivateKey) -> Result<(), WalletError> {
+    bls_secret_key(sk.expose()).map_err(|_| WalletError::InvalidPrivateKey("Invalid secret key".into()))?;
+    Ok(())
+}
+
+/// Reduces the 64-byte key to a scalar and loads it big-endian into blst, whose key wipes itself
+/// on drop.
+fn bls_secret_key(sk_64: &[u8; PRIVATE_KEY_LEN]) -> Result<BlsSecretKey, BLST_ERROR> {
+    let mut sk_be = Zeroizing::new(Scalar::from_bytes_wide(sk_64).to_bytes());
     sk_be.reverse();
-    BlsSecretKey::from_bytes(&sk_be).map_err(|_| WalletError::InvalidPrivateKey("Invalid secret key".into()))?;
-    Ok(arr)
+    BlsSecretKey::from_bytes(&*sk_be)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::G1Affine;
+
+    fn b58(bytes: &[u8]) -> String {
+        bs58::encode(bytes).into_string()
+    }
+
+    /// Compressed encoding of the candidate point with the given x coordinate (y chosen by the flag).
+    fn compressed_x(x: u8) -> [u8; PUBLIC_KEY_LEN] {
+        let mut bytes = [0u8; PUBLIC_KEY_LEN];
+        bytes[0] = 0x80;
+        bytes[PUBLIC_KEY_LEN - 1] = x;
+        bytes
+    }
+
+    #[test]
+    fn accepts_a_derived_public_key() {
+        let sk = PrivateKey::from_slice(&[7u8; PRIVATE_KEY_LEN]).unwrap();
+        let pk = public_key_bytes(&sk);
+        assert_eq!(check_public_key_base58(&b58(&pk)), Ok(pk));
+    }
+
+    #[test]
+    fn rejects_bad_base58() {
+        assert_eq!(check_public_key_base58("0OIl"), Err(PublicKeyError::BadBase58));
+    }
+
+    #[test]
+    fn rejects_the_wrong_length() {
+        let sk = PrivateKey::from_slice(&[7u8; PRIVATE_KEY_LEN]).unwrap();
+        let pk = public_key_bytes(&sk);
+        assert_eq!(check_public_key_base58(&b58(&pk[..47])), Err(PublicKeyError::WrongLength { len: 47 }));
+        assert_eq!(check_public_key_base58(&b58(&[1u8; 96])), Err(PublicKeyError::WrongLength { len: 96 }));
+    }
+
+    #[test]
+    fn rejects_the_identity_point() {
+        let mut identity = [0u8; PUBLIC_KEY_LEN];
+        identity[0] = 0xc0;
+        assert_eq!(check_public_key_base58(&b58(&identity)), Err(PublicKeyError::InvalidPoint));
+    }
+
+    #[test]
+    fn rejects_non_canonical_encodings() {
+        let sk = PrivateKey::from_slice(&[7u8; PRIVATE_KEY_LEN]).unwrap();
+        let pk = public_key_bytes(&sk);
+
+        // Compression flag cleared: 48 bytes claiming to be an uncompressed point.
+        let mut uncompressed_flag = pk;
+        uncompressed_flag[0] &= !0x80;
+        assert_eq!(check_public_key_base58(&b58(&uncompressed_flag)), Err(PublicKeyError::InvalidPoint));
+
+        // Infinity flag with stray bits in the coordinate.
+        let mut dirty_identity = [0u8; PUBLIC_KEY_LEN];
+        dirty_identity[0] = 0xc0;
+        dirty_identity[PUBLIC_KEY_LEN - 1] = 1;
+        assert_eq!(check_public_key_base58(&b58(&dirty_identity)), Err(PublicKeyError::InvalidPoint));
+
+        // x = 2^381 - 1 lies above the field modulus, so it is not a reduced coordinate.
+        let mut x_over_p = [0xffu8; PUBLIC_KEY_LEN];
+        x_over_p[0] = 0x80 | 0x1f;
+        assert_eq!(check_public_key_base58(&b58(&x_over_p)), Err(PublicKeyError::InvalidPoint));
+    }
+
+    #[test]
+    fn rejects_a_point_off_the_curve() {
+        let off_curve = (1..=u8::MAX)
+            .map(compressed_x)
+            .find(|b| bool::from(G1Affine::from_compressed_unchecked(b).is_none()))
+            .expect("some small x has no point");
+        assert_eq!(check_public_key_base58(&b58(&off_curve)), Err(PublicKeyError::InvalidPoint));
+    }
+
+    #[test]
+    fn rejects_a_point_outside_the_subgroup() {
+        let outside = (1..=u8::MAX)
+            .map(compressed_x)
+            .find(|b| {
+                bool::from(G1Affine::from_compressed_unchecked(b).is_some())
+                    && bool::from(G1Affine::from_compressed(b).is_none())
+            })
+            .expect("some small x lies on the curve but outside G1");
+        assert_eq!(check_public_key_base58(&b58(&outside)), Err(PublicKeyError::InvalidPoint));
+    }
 }
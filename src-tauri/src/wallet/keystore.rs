@@ -0,0 +1,265 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::storage;
+use rand::RngCore;
+use std::path::{Path, PathBuf};
+
+const INDEX_FILENAME: &str = "accounts.json";
+const ACCOUNTS_DIRNAME: &str = "accounts";
+const BACKUPS_DIRNAME: &str = "backups";
+/// The single-wallet layout used before the keystore; adopted as the first account on load.
+const LEGACY_WALLET_FILENAME: &str = "wallet.enc";
+const KEYSTORE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Account {
+    pub id: String,
+    pub label: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// Cached so accounts can be listed without unlocking. `None` only for a wallet adopted from
+    /// the legacy `wallet.enc` that has not been unlocked since.
+    pub public_key: Option<String>,
+    /// Set for accounts derived from another account's key with `keys::derive_child_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation: Option<Derivation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Derivation {
+    /// Id of the root account whose key the child was derived from.
+    pub root: String,
+    pub index: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AccountList {
+    pub selected: Option<String>,
+    pub accounts: Vec<Account>,
+}
+
+/// Index of accounts in `<wallet_dir>/accounts.json`; each key lives in `accounts/<id>.enc`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Keystore {
+    version: u32,
+    selected: Option<String>,
+    accounts: Vec<Account>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+impl Keystore {
+    pub fn load(dir: &Path) -> Result<Self, WalletError> {
+        let index_path = dir.join(INDEX_FILENAME);
+        if !index_path.exists() {
+            let mut keystore = Keystore {
+                version: KEYSTORE_VERSION,
+                selected: None,
+                accounts: Vec::new(),
+                dir: dir.to_path_buf(),
+            };
+            keystore.adopt_legacy_wallet()?;
+            return Ok(keystore);
+        }
+        let data = std::fs::read(&index_path).map_err(|e| WalletError::Storage(e.to_string()))?;
+        let mut keystore: Keystore = serde_json::from_slice(&data)
+            .map_err(|e| WalletError::CorruptFile(format!("Account index: {}", e)))?;
+        if keystore.version > KEYSTORE_VERSION {
+            return Err(WalletError::CorruptFile(format!(
+                "Account index version {} is newer than this app supports",
+                keystore.version
+            )));
+        }
+        keystore.dir = dir.to_path_buf();
+        Ok(keystore)
+    }
+
+    pub fn save(&self) -> Result<(), WalletError> {
+        let data = serde_json::to_vec_pretty(self).map_err(|e| WalletError::Storage(e.to_string()))?;
+        storage::write_atomic(&self.dir.join(INDEX_FILENAME), &data)
+    }
+
+    pub fn list(&self) -> AccountList {
+        AccountList {
+            selected: self.selected.clone(),
+            accounts: self.accounts.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn key_path(&self, id: &str) -> PathBuf {
+        self.dir.join(ACCOUNTS_DIRNAME).join(format!("{}.enc", id))
+    }
+
+    /// Where replaced key files are moved to instead of being overwritten.
+    pub fn backup_dir(&self) -> PathBuf {
+        self.dir.join(BACKUPS_DIRNAME)
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Account, WalletError> {
+        self.accounts
+            .iter()
+            .find(|a| a.id == id)
+            .ok_or_else(|| WalletError::AccountNotFound(id.to_string()))
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut Account, WalletError> {
+        self.accounts
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| WalletError::AccountNotFound(id.to_string()))
+    }
+
+    pub fn selected(&self) -> Result<&Account, WalletError> {
+        match &self.selected {
+            Some(id) => self.get(id),
+            None => Err(WalletError::NoWallet),
+        }
+    }
+
+    pub fn find_by_public_key(&self, public_key: &str) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|a| a.public_key.as_deref() == Some(public_key))
+    }
+
+    /// Registers a new account (its key file is written by the caller) and selects it.
+    pub fn add(&mut self, label: Option<&str>, public_key: String) -> Account {
+        let label = match label.map(str::trim) {
+            Some(l) if !l.is_empty() => l.to_string(),
+            _ => format!("Account {}", self.accounts.len() + 1),
+        };
+        let account = Account {
+            id: new_account_id(),
+            label,
+            created_at: now_secs(),
+            public_key: Some(public_key),
+            derivation: None,
+        };
+        self.selected = Some(account.id.clone());
+        self.accounts.push(account.clone());
+        account
+    }
+
+    /// Like `add`, for a child of `derivation.root`; the label defaults to `<root label> / <index>`.
+    pub fn add_derived(
+        &mut self,
+        label: Option<&str>,
+        public_key: String,
+        derivation: Derivation,
+    ) -> Result<Account, WalletError> {
+        let default_label = format!("{} / {}", self.get(&derivation.root)?.label, derivation.index);
+        let label = label.filter(|l| !l.trim().is_empty()).unwrap_or(&default_label);
+        let mut account = self.add(Some(label), public_key);
+        account.derivation = Some(derivation);
+        self.get_mut(&account.id)?.derivation = account.derivation.clone();
+        Ok(account)
+    }
+
+    /// The index after the highest child already derived from `root`.
+    pub fn next_child_index(&self, root: &str) -> u32 {
+        self.accounts
+            .iter()
+            .filter_map(|a| a.derivation.as_ref())
+            .filter(|d| d.root == root)
+            .map(|d| d.index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Re-registers an account from a backup under its original id; its key file is written by the
+    /// caller. Does not change the selection.
+    pub fn restore(&mut self, account: Account) {
+        self.accounts.push(account);
+    }
+
+    pub fn rename(&mut self, id: &str, label: &str) -> Result<(), WalletError> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(WalletError::InvalidSetting("Account label cannot be empty".into()));
+        }
+        self.get_mut(id)?.label = label.to_string();
+        Ok(())
+    }
+
+    pub fn select(&mut self, id: &str) -> Result<(), WalletError> {
+        self.get(id)?;
+        self.selected = Some(id.to_string());
+        Ok(())
+    }
+
+    pub fn set_public_key(&mut self, id: &str, public_key: String) -> Result<(), WalletError> {
+        self.get_mut(id)?.public_key = Some(public_key);
+        Ok(())
+    }
+
+    /// Drops the account from the index and moves its key file to `backup_dir`, returning where it
+    /// went. If it was selected, the first remaining account (if any) becomes selected.
+    pub fn remove(&mut self, id: &str) -> Result<Option<PathBuf>, WalletError> {
+        self.get(id)?;
+        let path = self.key_path(id);
+        let backup = if path.exists() {
+            Some(storage::backup_file(&path, &self.backup_dir())?)
+        } else {
+            None
+        };
+        self.accounts.retain(|a| a.id != id);
+        if self.selected.as_deref() == Some(id) {
+            self.selected = self.accounts.first().map(|a| a.id.clone());
+        }
+        Ok(backup)
+    }
+
+    fn adopt_legacy_wallet(&mut self) -> Result<(), WalletError> {
+        let legacy_path = self.dir.join(LEGACY_WALLET_FILENAME);
+        if !legacy_path.exists() {
+            return Ok(());
+        }
+        let account = Account {
+            id: new_account_id(),
+            label: "Main account".to_string(),
+            created_at: std::fs::metadata(&legacy_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or_else(now_secs),
+            public_key: None,
+            derivation: None,
+        };
+        let key_path = self.key_path(&account.id);
+        if let Some(parent) = key_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| WalletError::Storage(e.to_string()))?;
+        }
+        std::fs::rename(&legacy_path, &key_path).map_err(|e| WalletError::Storage(e.to_string()))?;
+        self.selected = Some(account.id.clone());
+        self.accounts.push(account);
+        if let Err(e) = self.save() {
+            // Put the file back so the wallet is still found next time.
+            let _ = std::fs::rename(&key_path, &legacy_path);
+            self.selected = None;
+            self.accounts.clear();
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+fn new_account_id() -> String {
+    format!("{:016x}", rand::rng().next_u64())
+}
+
+/// Whether `id` has the shape `new_account_id` produces. Ids from outside the wallet, such as a
+/// backup, must pass this before they are used in a path.
+pub fn is_account_id(id: &str) -> bool {
+    id.len() == 16 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,135 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::keys;
+use crate::wallet::signer::Signer;
+use base64::Engine;
+
+/// Domain separation tag for signed messages. Transactions are signed under
+/// `AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_`; because the tag is part of hashing to the
+/// curve, a message signature never verifies as a transaction signature and vice versa, whatever
+/// bytes the message holds.
+pub const DST_MESSAGE: &[u8] = b"AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_MSG_";
+
+/// A message as the frontend describes it; the signature covers the decoded bytes.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum MessageInput {
+    /// UTF-8 text, signed as its bytes without normalisation.
+    Text(String),
+    /// Binary data written as Base58.
+    Base58(String),
+    /// Binary data written as standard padded Base64.
+    Base64(String),
+}
+
+impl MessageInput {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, WalletError> {
+        match self {
+            MessageInput::Text(s) => Ok(s.as_bytes().to_vec()),
+            MessageInput::Base58(s) => bs58::decode(s.trim())
+                .into_vec()
+                .map_err(|e| WalletError::InvalidMessage(format!("Invalid Base58: {}", e))),
+            MessageInput::Base64(s) => base64::engine::general_purpose::STANDARD
+                .decode(s.trim())
+                .map_err(|e| WalletError::InvalidMessage(format!("Invalid Base64: {}", e))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SignedMessage {
+    pub public_key: String,
+    /// 96-byte G2 signature, Base58.
+    pub signature: String,
+    pub dst: String,
+}
+
+pub fn sign(signer: &dyn Signer, message: &[u8]) -> Result<SignedMessage, WalletError> {
+    let signature = signer.sign(message, DST_MESSAGE)?;
+    Ok(SignedMessage {
+        public_key: bs58::encode(signer.public_key()).into_string(),
+        signature: bs58::encode(signature).into_string(),
+        dst: String::from_utf8_lossy(DST_MESSAGE).into_owned(),
+    })
+}
+
+/// `Ok(false)` when the signature does not verify, including bytes that are not a curve point;
+/// input that does not decode to a 48-byte key or 96-byte signature is an error.
+pub fn verify(public_key_base58: &str, message: &[u8], signature_base58: &str) -> Result<bool, WalletError> {
+    let public_key = keys::decode_public_key_base58(public_key_base58)?;
+    let signature: [u8; 96] = bs58::decode(signature_base58.trim())
+        .into_vec()
+        .map_err(|e| WalletError::InvalidMessage(format!("Invalid signature: {}", e)))?
+        .try_into()
+        .map_err(|v: Vec<u8>| {
+            WalletError::InvalidMessage(format!("Signature must be 96 bytes (Base58 decoded), got {}", v.len()))
+        })?;
+    match keys::verify_with_dst(&public_key, message, &signature, DST_MESSAGE) {
+        Ok(()) => Ok(true),
+        Err(WalletError::InvalidSignature) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amadeus_tx::{Transaction, TxAction};
+    use crate::wallet::secret::PrivateKey;
+
+    fn key() -> PrivateKey {
+        PrivateKey::from_slice(&[5; 64]).unwrap()
+    }
+
+    fn signature_bytes(signed: &SignedMessage) -> [u8; 96] {
+        bs58::decode(&signed.signature).into_vec().unwrap().try_into().unwrap()
+    }
+
+    /// An unsigned transaction from `sk`, whose hash both tests sign.
+    fn transaction(sk: &PrivateKey) -> Transaction {
+        Transaction {
+            signer: sk.public_key(),
+            nonce: 1,
+            action: TxAction::call(b"Coin", "transfer", vec![vec![3; 48], b"1".to_vec(), b"AMA".to_vec()]),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_input_form() {
+        let sk = key();
+        let bytes = b"hello \xff world".to_vec();
+        let inputs = [
+            MessageInput::Text("hello world".to_string()),
+            MessageInput::Base58(bs58::encode(&bytes).into_string()),
+            MessageInput::Base64(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+        ];
+        for input in inputs {
+            let message = input.to_bytes().unwrap();
+            let signed = sign(&sk, &message).unwrap();
+            assert!(verify(&signed.public_key, &message, &signed.signature).unwrap());
+            assert!(!verify(&signed.public_key, b"something else", &signed.signature).unwrap());
+        }
+        assert_eq!(MessageInput::Base58(bs58::encode(&bytes).into_string()).to_bytes().unwrap(), bytes);
+        assert!(matches!(MessageInput::Base58("0OIl".to_string()).to_bytes(), Err(WalletError::InvalidMessage(_))));
+        assert!(matches!(MessageInput::Base64("@@".to_string()).to_bytes(), Err(WalletError::InvalidMessage(_))));
+    }
+
+    #[test]
+    fn message_signature_is_not_a_transaction_signature() {
+        let sk = key();
+        let tx = transaction(&sk);
+        let signed = sign(&sk, &tx.hash()).unwrap();
+        assert!(verify(&signed.public_key, &tx.hash(), &signed.signature).unwrap());
+        assert!(tx.verify_cosignature(&sk.public_key(), &signature_bytes(&signed)).is_err());
+        assert!(tx.with_signature(signature_bytes(&signed)).verify().is_err());
+    }
+
+    #[test]
+    fn transaction_signature_is_not_a_message_signature() {
+        let sk = key();
+        let signed_tx = transaction(&sk).sign(&sk).unwrap();
+        assert!(signed_tx.verify().is_ok());
+        let public_key = bs58::encode(sk.public_key()).into_string();
+        let signature = bs58::encode(signed_tx.signature).into_string();
+        assert!(!verify(&public_key, &signed_tx.hash, &signature).unwrap());
+    }
+}
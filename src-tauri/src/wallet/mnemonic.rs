@@ -0,0 +1,206 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::keys;
+use crate::wallet::secret::{PrivateKey, SecretString, PRIVATE_KEY_LEN};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+const WORDLIST: &str = include_str!("bip39_english.txt");
+const BITS_PER_WORD: usize = 11;
+const CHECKSUM_LEN: usize = 2;
+const PASSPHRASE_SALT: &[u8] = b"amadeus-wallet-mnemonic";
+
+fn words() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.lines().collect())
+}
+
+/// Encodes the key as 48 words from the BIP39 English list.
+///
+/// Standard BIP39 tops out at 256 bits of entropy and stretches the words into a seed through a
+/// one-way PBKDF2 step, so it cannot reproduce a key that already exists. Here the 64-byte key is
+/// the entropy itself: its 512 bits plus the first 16 bits of their SHA-256 are read as 48 11-bit
+/// word indices, and decoding gives back exactly the bytes `Scalar::from_bytes_wide` consumes.
+///
+/// A non-empty passphrase XORs the key with an Argon2id pad first, so the same words restore a
+/// different key under a different passphrase.
+pub fn to_mnemonic(sk: &PrivateKey, passphrase: &str) -> Result<SecretString, WalletError> {
+    let mut data = Zeroizing::new(Vec::with_capacity(PRIVATE_KEY_LEN + CHECKSUM_LEN));
+    data.extend_from_slice(sk.expose());
+    apply_passphrase(&mut data, passphrase)?;
+    let checksum = Sha256::digest(&data[..]);
+    data.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+    Ok(encode_words(&data))
+}
+
+/// Words may be separated by any whitespace and are matched case-insensitively.
+pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<PrivateKey, WalletError> {
+    let mut data = decode_words(phrase, PRIVATE_KEY_LEN + CHECKSUM_LEN)?;
+    let (key, checksum) = data.split_at_mut(PRIVATE_KEY_LEN);
+    if Sha256::digest(&*key)[..CHECKSUM_LEN] != *checksum {
+        return Err(WalletError::InvalidMnemonic(
+            "checksum mismatch; check the words and their order".into(),
+        ));
+    }
+    apply_passphrase(key, passphrase)?;
+    let sk = PrivateKey::from_slice(key).ok_or_else(|| WalletError::Crypto("Bad key length".into()))?;
+    keys::validate_private_key(&sk)?;
+    Ok(sk)
+}
+
+/// Number of words `encode_words` produces for `len` bytes.
+pub fn word_count(len: usize) -> usize {
+    (len * 8).div_ceil(BITS_PER_WORD)
+}
+
+/// Writes `data` as BIP39 English words, 11 bits each, zero-padding the last word.
+pub fn encode_words(data: &[u8]) -> SecretString {
+    let list = words();
+    let count = word_count(data.len());
+    // Sized up front (words are at most 8 letters) so growing never leaves copies behind.
+    let mut phrase = Zeroizing::new(String::with_capacity(count * 9));
+    for i in 0..count {
+        if i > 0 {
+            phrase.push(' ');
+        }
+        phrase.push_str(list[read_bits(data, i * BITS_PER_WORD)]);
+    }
+    SecretString::new(std::mem::take(&mut *phrase))
+}
+
+/// Reverses `encode_words` for data of exactly `len` bytes. The padding bits must be zero.
+pub fn decode_words(phrase: &str, len: usize) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+    let list = words();
+    let given: Vec<&str> = phrase.split_whitespace().collect();
+    let count = word_count(len);
+    if given.len() != count {
+        return Err(WalletError::InvalidMnemonic(format!(
+            "expected {} words, got {}",
+            count,
+            given.len()
+        )));
+    }
+    let mut data = Zeroizing::new(vec![0u8; (count * BITS_PER_WORD).div_ceil(8)]);
+    for (i, word) in given.iter().enumerate() {
+        let word = Zeroizing::new(word.to_lowercase());
+        let index = list
+            .binary_search(&word.as_str())
+            .map_err(|_| WalletError::InvalidMnemonic(format!("word {} is not in the word list", i + 1)))?;
+        write_bits(&mut data, i * BITS_PER_WORD, index);
+    }
+    if (len * 8..count * BITS_PER_WORD).any(|bit| read_bit(&data, bit)) {
+        return Err(WalletError::InvalidMnemonic("the last word is wrong".into()));
+    }
+    data.truncate(len);
+    Ok(data)
+}
+
+/// XORs `key` with a 64-byte Argon2id pad of the passphrase. Its own inverse; empty is a no-op.
+fn apply_passphrase(key: &mut [u8], passphrase: &str) -> Result<(), WalletError> {
+    if passphrase.is_empty() {
+        return Ok(());
+    }
+    let params = argon2::Params::new(
+        argon2::Params::DEFAULT_M_COST,
+        argon2::Params::DEFAULT_T_COST,
+        argon2::Params::DEFAULT_P_COST,
+        Some(PRIVATE_KEY_LEN),
+    )
+    .map_err(|e| WalletError::Crypto(e.to_string()))?;
+    let mut pad = Zeroizing::new([0u8; PRIVATE_KEY_LEN]);
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), PASSPHRASE_SALT, &mut *pad)
+        .map_err(|e| WalletError::Crypto(e.to_string()))?;
+    for (k, p) in key.iter_mut().zip(pad.iter()) {
+        *k ^= p;
+    }
+    Ok(())
+}
+
+fn read_bit(data: &[u8], bit: usize) -> bool {
+    data.get(bit / 8).is_some_and(|b| (b >> (7 - bit % 8)) & 1 == 1)
+}
+
+/// The 11-bit big-endian value starting at bit `start`; bits past the end read as zero.
+fn read_bits(data: &[u8], start: usize) -> usize {
+    (0..BITS_PER_WORD).fold(0, |acc, i| (acc << 1) | usize::from(read_bit(data, start + i)))
+}
+
+fn write_bits(data: &mut [u8], start: usize, value: usize) {
+    for i in 0..BITS_PER_WORD {
+        if (value >> (BITS_PER_WORD - 1 - i)) & 1 == 1 {
+            let bit = start + i;
+            data[bit / 8] |= 1 << (7 - bit % 8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> PrivateKey {
+        keys::decode_private_key_base58(keys::generate_private_key_base58().unwrap().expose()).unwrap()
+    }
+
+    /// The phrase with its last word swapped for the next one in the list.
+    fn with_last_word_changed(phrase: &str) -> String {
+        let mut words: Vec<&str> = phrase.split(' ').collect();
+        let last = words.pop().unwrap();
+        let index = self::words().binary_search(&last).unwrap();
+        words.push(self::words()[(index + 1) % self::words().len()]);
+        words.join(" ")
+    }
+
+    #[test]
+    fn round_trips() {
+        let sk = key();
+        let phrase = to_mnemonic(&sk, "").unwrap();
+        assert_eq!(phrase.expose().split(' ').count(), 48);
+        assert_eq!(from_mnemonic(phrase.expose(), "").unwrap().expose(), sk.expose());
+    }
+
+    #[test]
+    fn round_trips_with_a_passphrase() {
+        let sk = key();
+        let phrase = to_mnemonic(&sk, "correct horse").unwrap();
+        assert_ne!(phrase.expose(), to_mnemonic(&sk, "").unwrap().expose());
+        assert_eq!(from_mnemonic(phrase.expose(), "correct horse").unwrap().expose(), sk.expose());
+        let other = from_mnemonic(phrase.expose(), "battery staple").map(|k| *k.expose());
+        assert_ne!(other.ok(), Some(*sk.expose()));
+    }
+
+    #[test]
+    fn ignores_case_and_spacing() {
+        let sk = key();
+        let phrase = to_mnemonic(&sk, "").unwrap();
+        let messy = format!("  {}\n", phrase.expose().to_uppercase().replace(' ', " \t "));
+        assert_eq!(from_mnemonic(&messy, "").unwrap().expose(), sk.expose());
+    }
+
+    #[test]
+    fn rejects_a_wrong_last_word() {
+        let phrase = to_mnemonic(&key(), "").unwrap();
+        let err = from_mnemonic(&with_last_word_changed(phrase.expose()), "").unwrap_err();
+        assert!(matches!(err, WalletError::InvalidMnemonic(m) if m.contains("checksum")));
+    }
+
+    #[test]
+    fn rejects_unknown_words_and_wrong_counts() {
+        let phrase = to_mnemonic(&key(), "").unwrap();
+        let mut words: Vec<&str> = phrase.expose().split(' ').collect();
+        assert!(from_mnemonic(&words[1..].join(" "), "").is_err());
+        words[0] = "notaword";
+        assert!(from_mnemonic(&words.join(" "), "").is_err());
+    }
+
+    #[test]
+    fn rejects_set_padding_bits() {
+        // 73 bytes take 54 words, leaving 10 padding bits in the last one.
+        let data = [0xa5; 73];
+        let phrase = encode_words(&data);
+        assert_eq!(decode_words(phrase.expose(), data.len()).unwrap()[..], data[..]);
+        let err = decode_words(&with_last_word_changed(phrase.expose()), data.len()).unwrap_err();
+        assert!(matches!(err, WalletError::InvalidMnemonic(m) if m.contains("last word")));
+    }
+}
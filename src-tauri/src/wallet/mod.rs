@@ -1,8 +1,37 @@
+mod backup;
 mod error;
 pub mod keys;
+mod keystore;
+mod message;
+mod multisig;
+mod nonce;
+mod offline;
+mod mnemonic;
+mod secret;
+mod session;
+mod shamir;
+mod signer;
 mod service;
 mod storage;
 
+pub use backup::{BackupMetadata, BackupSettings, RestoredBackup};
 pub use error::WalletError;
-pub use keys::{public_key_from_private_base58, validate_public_key_base58};
-pub use service::{change_password, create_wallet, has_wallet, import_wallet, sign_transaction, unlock_wallet, wallet_file_path};
+pub use keys::{public_key_from_private_base58, validate_public_key_base58, PublicKeyValidation};
+pub use keystore::{Account, AccountList};
+pub use message::{MessageInput, SignedMessage};
+pub use multisig::{Cosigner, MultisigStatus, PartialTx};
+pub use secret::SecretString;
+pub use service::{
+    benchmark_kdf, change_password, create_multisig_transfer, create_wallet, delete_account, derive_account,
+    export_backup, export_mnemonic, export_unsigned_contract_call, export_unsigned_transfer, finalize_multisig,
+    has_wallet, import_backup, import_mnemonic, import_wallet, inspect_unsigned_transaction, kdf_cost,
+    list_accounts, load_signed_transaction_file, lock_wallet, merge_multisig, multisig_address, multisig_proof,
+    multisig_status, nonce_status, preview_transaction, recover_from_shares, rename_account, scan_derived_accounts,
+    select_account, session_status, set_auto_lock_timeout, set_kdf_preset, sign_contract_call, sign_message,
+    sign_multisig, sign_transaction, sign_transaction_file, split_key, sync_nonce, unlock_wallet, verify_message,
+    verify_signed_transaction, wallet_file_path, KdfBenchmark, NonceInfo,
+};
+pub use session::{spawn_auto_lock, Session, SessionStatus};
+pub use shamir::ShareEncoding;
+pub use signer::Signer;
+pub use storage::{write_atomic, KdfCost, KdfPreset};
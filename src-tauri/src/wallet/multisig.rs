@@ -0,0 +1,314 @@
+use crate::amadeus_tx::{SignedTx, Transaction};
+use crate::wallet::error::WalletError;
+use crate::wallet::keys;
+use crate::wallet::signer::Signer;
+use std::collections::BTreeMap;
+
+const MULTISIG_FORMAT: &str = "amadeus-multisig";
+const MULTISIG_VERSION: u32 = 1;
+
+/// Proofs of possession sign the co-signer's own public key under a tag of their own, so a proof
+/// can never double as a transaction or message signature.
+const DST_POP: &[u8] = b"AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A co-signer's public key with its proof of possession, both Base58.
+///
+/// The shared address is the plain sum of the co-signers' keys, so without the proof a co-signer
+/// could pick its key as `g^m` minus the others' and end up with an address it alone can sign
+/// for (a rogue-key attack). The proof shows the key's owner knows its private key, which such a
+/// key's author does not.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Cosigner {
+    pub public_key: String,
+    pub proof: String,
+}
+
+/// A transaction collecting co-signatures, passed from co-signer to co-signer as JSON.
+///
+/// The chain checks one signature against `tx.signer`, so a shared account's address is the
+/// aggregate of its co-signers' public keys and every one of them has to sign before the
+/// aggregated signature verifies: this is n-of-n. A k-of-n threshold would need the co-signers to
+/// share one key through a distributed key generation instead of summing their own, which is out
+/// of scope here.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PartialTx {
+    pub format: String,
+    pub version: u32,
+    /// The packed unsigned `tx`, Base58.
+    pub tx: String,
+    /// Sorted by public key.
+    pub cosigners: Vec<Cosigner>,
+    /// Co-signer public key to its signature over the tx hash, both Base58.
+    #[serde(default)]
+    pub signatures: BTreeMap<String, String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct MultisigStatus {
+    pub tx: Transaction,
+    /// Base58.
+    pub hash: String,
+    pub signed: Vec<String>,
+    pub missing: Vec<String>,
+    pub complete: bool,
+}
+
+/// `signer`'s public key and proof of possession, to hand to whoever sets up the shared account.
+pub fn prove(signer: &dyn Signer) -> Result<Cosigner, WalletError> {
+    let public_key = signer.public_key();
+    let proof = signer.sign(&public_key, DST_POP)?;
+    Ok(Cosigner {
+        public_key: bs58::encode(public_key).into_string(),
+        proof: bs58::encode(proof).into_string(),
+    })
+}
+
+/// The shared account address for a set of co-signers; the order of the keys does not matter.
+pub fn address(cosigners: &[Cosigner]) -> Result<String, WalletError> {
+    let (_, aggregate) = cosigner_set(cosigners)?;
+    Ok(bs58::encode(aggregate).into_string())
+}
+
+/// Starts collecting signatures for `tx`, whose signer must be the co-signers' shared address.
+pub fn create(tx: &Transaction, cosigners: &[Cosigner]) -> Result<PartialTx, WalletError> {
+    let (set, aggregate) = cosigner_set(cosigners)?;
+    if tx.signer != aggregate {
+        return Err(invalid("the transaction signer is not the co-signers' shared address"));
+    }
+    Ok(PartialTx {
+        format: MULTISIG_FORMAT.to_string(),
+        version: MULTISIG_VERSION,
+        tx: bs58::encode(tx.encode()).into_string(),
+        cosigners: set
+            .iter()
+            .map(|(pk, proof)| Cosigner {
+                public_key: bs58::encode(pk).into_string(),
+                proof: bs58::encode(proof).into_string(),
+            })
+            .collect(),
+        signatures: BTreeMap::new(),
+    })
+}
+
+/// Adds `signer`'s signature, replacing any earlier one from the same key.
+pub fn sign(partial: &PartialTx, signer: &dyn Signer) -> Result<PartialTx, WalletError> {
+    let (tx, cosigners, _) = parse(partial)?;
+    let public_key = signer.public_key();
+    if !cosigners.contains(&public_key) {
+        return Err(invalid("the selected account is not a co-signer of this transaction"));
+    }
+    let signature = tx.cosign(signer)?;
+    let mut signed = partial.clone();
+    signed.signatures.insert(
+        bs58::encode(public_key).into_string(),
+        bs58::encode(signature).into_string(),
+    );
+    Ok(signed)
+}
+
+/// Combines copies of the same transaction that were signed in parallel.
+pub fn merge(partials: &[PartialTx]) -> Result<PartialTx, WalletError> {
+    let (first, rest) = partials.split_first().ok_or_else(|| invalid("nothing to merge"))?;
+    let (tx, cosigners, _) = parse(first)?;
+    let mut merged = first.clone();
+    for other in rest {
+        let (other_tx, other_cosigners, _) = parse(other)?;
+        if other_tx != tx || other_cosigners != cosigners {
+            return Err(invalid("the copies are of different transactions"));
+        }
+        merged.signatures.extend(other.signatures.clone());
+    }
+    Ok(merged)
+}
+
+pub fn status(partial: &PartialTx) -> Result<MultisigStatus, WalletError> {
+    let (tx, cosigners, signatures) = parse(partial)?;
+    let (signed, missing): (Vec<&[u8; 48]>, Vec<&[u8; 48]>) =
+        cosigners.iter().partition(|pk| signatures.contains_key(*pk));
+    let encode = |pks: Vec<&[u8; 48]>| pks.into_iter().map(|pk| bs58::encode(pk).into_string()).collect();
+    Ok(MultisigStatus {
+        hash: bs58::encode(tx.hash()).into_string(),
+        complete: missing.is_empty(),
+        tx,
+        signed: encode(signed),
+        missing: encode(missing),
+    })
+}
+
+/// Aggregates the signatures into a submittable transaction once every co-signer has signed
+/// (n-of-n; a partial set of signatures never verifies against the shared address).
+pub fn finalize(partial: &PartialTx) -> Result<SignedTx, WalletError> {
+    let (tx, cosigners, signatures) = parse(partial)?;
+    let missing = cosigners.len() - signatures.len();
+    if missing > 0 {
+        return Err(invalid(&format!("{} of {} co-signers have not signed", missing, cosigners.len())));
+    }
+    let signatures: Vec<[u8; 96]> = signatures.into_values().collect();
+    let signed = tx.with_signature(keys::aggregate_signatures(&signatures)?);
+    signed.verify()?;
+    Ok(signed)
+}
+
+type Parsed = (Transaction, Vec<[u8; 48]>, BTreeMap<[u8; 48], [u8; 96]>);
+
+/// Decodes and checks everything in `partial`, including every signature present.
+fn parse(partial: &PartialTx) -> Result<Parsed, WalletError> {
+    if partial.format != MULTISIG_FORMAT {
+        return Err(invalid("not a multisig transaction"));
+    }
+    if partial.version > MULTISIG_VERSION {
+        return Err(WalletError::InvalidMultisig(format!(
+            "version {} is newer than this app supports",
+            partial.version
+        )));
+    }
+    let packed = bs58::decode(partial.tx.trim())
+        .into_vec()
+        .map_err(|e| WalletError::InvalidTransaction(e.to_string()))?;
+    let tx = Transaction::decode(&packed)?;
+    let (set, aggregate) = cosigner_set(&partial.cosigners)?;
+    let cosigners: Vec<[u8; 48]> = set.into_iter().map(|(pk, _)| pk).collect();
+    if tx.signer != aggregate {
+        return Err(invalid("the transaction signer is not the co-signers' shared address"));
+    }
+    let mut signatures = BTreeMap::new();
+    for (pk, sig) in &partial.signatures {
+        let pk = keys::decode_public_key_base58(pk)?;
+        if !cosigners.contains(&pk) {
+            return Err(invalid("signed by a key that is not a co-signer"));
+        }
+        let sig: [u8; 96] = bs58::decode(sig.trim())
+            .into_vec()
+            .ok()
+            .and_then(|v| v.try_into().ok())
+            .ok_or_else(|| invalid("a signature is not 96 bytes of Base58"))?;
+        tx.verify_cosignature(&pk, &sig).map_err(|_| {
+            WalletError::InvalidMultisig(format!("bad signature from {}", bs58::encode(pk).into_string()))
+        })?;
+        signatures.insert(pk, sig);
+    }
+    Ok((tx, cosigners, signatures))
+}
+
+type CosignerSet = (Vec<([u8; 48], [u8; 96])>, [u8; 48]);
+
+/// Decodes, sorts and aggregates co-signer keys after checking each one's proof of possession.
+/// At least two distinct keys are required.
+fn cosigner_set(cosigners: &[Cosigner]) -> Result<CosignerSet, WalletError> {
+    let mut set = cosigners.iter().map(check_proof).collect::<Result<Vec<_>, _>>()?;
+    set.sort_by_key(|(pk, _)| *pk);
+    if set.windows(2).any(|w| w[0].0 == w[1].0) {
+        return Err(invalid("a co-signer is listed twice"));
+    }
+    if set.len() < 2 {
+        return Err(invalid("at least two co-signers are needed"));
+    }
+    let keys: Vec<[u8; 48]> = set.iter().map(|(pk, _)| *pk).collect();
+    let aggregate = keys::aggregate_public_keys(&keys)?;
+    Ok((set, aggregate))
+}
+
+fn check_proof(cosigner: &Cosigner) -> Result<([u8; 48], [u8; 96]), WalletError> {
+    let pk = keys::decode_public_key_base58(&cosigner.public_key)?;
+    let proof: [u8; 96] = bs58::decode(cosigner.proof.trim())
+        .into_vec()
+        .ok()
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| invalid("a proof of possession is not 96 bytes of Base58"))?;
+    keys::verify_with_dst(&pk, &pk, &proof, DST_POP).map_err(|_| {
+        WalletError::InvalidMultisig(format!(
+            "bad proof of possession for {}",
+            bs58::encode(pk).into_string()
+        ))
+    })?;
+    Ok((pk, proof))
+}
+
+fn invalid(msg: &str) -> WalletError {
+    WalletError::InvalidMultisig(msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amadeus_tx::TxAction;
+    use crate::wallet::secret::PrivateKey;
+
+    const DST_TEST: &[u8] = b"AMADEUS_TEST_";
+
+    fn key(n: u8) -> PrivateKey {
+        PrivateKey::from_slice(&[n; 64]).unwrap()
+    }
+
+    fn proofs(sks: &[&PrivateKey]) -> Vec<Cosigner> {
+        sks.iter().map(|sk| prove(*sk).unwrap()).collect()
+    }
+
+    fn transfer(cosigners: &[Cosigner]) -> PartialTx {
+        let tx = Transaction {
+            signer: keys::decode_public_key_base58(&address(cosigners).unwrap()).unwrap(),
+            nonce: 1,
+            action: TxAction::call(b"Coin", "transfer", vec![vec![3; 48], b"1000".to_vec(), b"AMA".to_vec()]),
+        };
+        create(&tx, cosigners).unwrap()
+    }
+
+    #[test]
+    fn accepts_valid_proofs_of_possession() {
+        let (a, b) = (key(1), key(2));
+        for cosigner in proofs(&[&a, &b]) {
+            assert!(check_proof(&cosigner).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_a_cosigner_without_a_valid_proof() {
+        let mut cosigners = proofs(&[&key(1), &key(2)]);
+        let other_proof = cosigners[1].proof.clone();
+        cosigners[0].proof = String::new();
+        assert!(address(&cosigners).is_err());
+        cosigners[0].proof = other_proof;
+        assert!(matches!(address(&cosigners), Err(WalletError::InvalidMultisig(m)) if m.contains("proof")));
+    }
+
+    #[test]
+    fn aggregated_signature_verifies_against_aggregated_key() {
+        let sks = [key(1), key(2), key(3)];
+        let msg = b"one message";
+        let pks: Vec<[u8; 48]> = sks.iter().map(keys::public_key_bytes).collect();
+        let sigs: Vec<[u8; 96]> = sks.iter().map(|sk| keys::sign_with_dst(sk, msg, DST_TEST).unwrap()).collect();
+        let pk = keys::aggregate_public_keys(&pks).unwrap();
+        let sig = keys::aggregate_signatures(&sigs).unwrap();
+        assert!(keys::verify_with_dst(&pk, msg, &sig, DST_TEST).is_ok());
+        let partial = keys::aggregate_signatures(&sigs[..2]).unwrap();
+        assert!(keys::verify_with_dst(&pk, msg, &partial, DST_TEST).is_err());
+    }
+
+    #[test]
+    fn finalizes_once_every_cosigner_has_signed() {
+        let (a, b) = (key(1), key(2));
+        let partial = transfer(&proofs(&[&a, &b]));
+        let half = sign(&partial, &a).unwrap();
+        assert!(!status(&half).unwrap().complete);
+        assert!(finalize(&half).is_err());
+
+        let full = sign(&half, &b).unwrap();
+        assert!(status(&full).unwrap().complete);
+        let signed = finalize(&full).unwrap();
+        assert!(signed.verify().is_ok());
+    }
+
+    #[test]
+    fn merges_copies_signed_in_parallel() {
+        let (a, b) = (key(1), key(2));
+        let partial = transfer(&proofs(&[&a, &b]));
+        let merged = merge(&[sign(&partial, &a).unwrap(), sign(&partial, &b).unwrap()]).unwrap();
+        assert!(finalize(&merged).unwrap().verify().is_ok());
+    }
+
+    #[test]
+    fn rejects_a_signature_from_outside_the_set() {
+        let partial = transfer(&proofs(&[&key(1), &key(2)]));
+        assert!(sign(&partial, &key(3)).is_err());
+    }
+}
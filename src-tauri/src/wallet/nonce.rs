@@ -0,0 +1,217 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::storage;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+const NONCES_FILE: &str = "nonces.json";
+/// How far past this machine's clock a nonce may be: enough for clocks that disagree, but a node
+/// reporting (or a user typing) something absurd cannot push every later nonce out of reach.
+const MAX_NONCE_AHEAD: i128 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Held from reading the file to writing it back, so two sends in quick succession can never be
+/// handed the same nonce.
+static NONCE_LOCK: Mutex<()> = Mutex::new(());
+
+/// What is known about one signer's nonces, keyed by Base58 public key in `nonces.json`.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct NonceState {
+    /// The highest nonce this wallet has put in a transaction.
+    #[serde(default)]
+    pub last_used: Option<i128>,
+    /// The node's nonce for the account when it was last synced.
+    #[serde(default)]
+    pub chain: Option<i128>,
+}
+
+impl NonceState {
+    /// Nonces are nanosecond timestamps, but never at or below one already used here or on chain,
+    /// so a clock that jumps backwards cannot produce a repeat.
+    pub fn next(&self) -> Result<i128, WalletError> {
+        let mut next = now();
+        for n in [self.last_used, self.chain].into_iter().flatten() {
+            let after = n
+                .checked_add(1)
+                .ok_or_else(|| WalletError::InvalidNonce(format!("no nonce left after {}", n)))?;
+            next = next.max(after);
+        }
+        Ok(next)
+    }
+}
+
+pub fn state(dir: &Path, signer: &str) -> Result<NonceState, WalletError> {
+    let _guard = NONCE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(load(dir)?.get(signer).copied().unwrap_or_default())
+}
+
+/// Reserves the nonce for a new transaction from `signer`. An explicit nonce (to replace a pending
+/// transaction) is used as given as long as the chain has not passed it; either way later
+/// automatic nonces stay above it.
+pub fn allocate(dir: &Path, signer: &str, explicit: Option<i128>) -> Result<i128, WalletError> {
+    let _guard = NONCE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut nonces = load(dir)?;
+    let entry = nonces.entry(signer.to_string()).or_default();
+    let nonce = match explicit {
+        Some(n) if n <= 0 => {
+            return Err(WalletError::InvalidNonce("nonce must be positive".into()));
+        }
+        Some(n) if entry.chain.is_some_and(|chain| n <= chain) => {
+            return Err(WalletError::InvalidNonce(format!(
+                "nonce {} has already been used on chain (last {})",
+                n,
+                entry.chain.unwrap_or_default()
+            )));
+        }
+        Some(n) if n > now() + MAX_NONCE_AHEAD => {
+            return Err(WalletError::InvalidNonce(format!("nonce {} is too far in the future", n)));
+        }
+        Some(n) => n,
+        None => entry.next()?,
+    };
+    entry.last_used = entry.last_used.max(Some(nonce));
+    save(dir, &nonces)?;
+    Ok(nonce)
+}
+
+/// Records the node's nonce for `signer`, returning the updated state. A nonce no honest node
+/// could report is refused rather than stored.
+pub fn record_chain(dir: &Path, signer: &str, chain: i128) -> Result<NonceState, WalletError> {
+    if chain < 0 || chain > now() + MAX_NONCE_AHEAD {
+        return Err(WalletError::InvalidNonce(format!("implausible nonce {} from the node", chain)));
+    }
+    let _guard = NONCE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut nonces = load(dir)?;
+    let entry = nonces.entry(signer.to_string()).or_default();
+    entry.chain = Some(chain);
+    let state = *entry;
+    save(dir, &nonces)?;
+    Ok(state)
+}
+
+fn now() -> i128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i128)
+        .unwrap_or(0)
+}
+
+fn load(dir: &Path) -> Result<BTreeMap<String, NonceState>, WalletError> {
+    match std::fs::read(dir.join(NONCES_FILE)) {
+        Ok(data) => serde_json::from_slice(&data).map_err(|e| WalletError::CorruptFile(e.to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(WalletError::Storage(e.to_string())),
+    }
+}
+
+fn save(dir: &Path, nonces: &BTreeMap<String, NonceState>) -> Result<(), WalletError> {
+    let data = serde_json::to_vec_pretty(nonces).map_err(|e| WalletError::Storage(e.to_string()))?;
+    storage::write_atomic(&dir.join(NONCES_FILE), &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const HOUR: i128 = 60 * 60 * 1_000_000_000;
+    const SIGNER: &str = "signer";
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("amadeus-nonce-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn next_follows_the_clock_when_nothing_is_ahead_of_it() {
+        let before = now();
+        let state = NonceState {
+            last_used: Some(1),
+            chain: Some(2),
+        };
+        assert!(state.next().unwrap() >= before);
+    }
+
+    #[test]
+    fn next_stays_above_nonces_ahead_of_the_clock() {
+        // A clock that went backwards looks the same as nonces recorded in the future.
+        let ahead = now() + HOUR;
+        let last_used = NonceState {
+            last_used: Some(ahead),
+            chain: None,
+        };
+        assert_eq!(last_used.next().unwrap(), ahead + 1);
+        let chain = NonceState {
+            last_used: Some(ahead),
+            chain: Some(ahead + 5),
+        };
+        assert_eq!(chain.next().unwrap(), ahead + 6);
+    }
+
+    #[test]
+    fn next_refuses_to_overflow() {
+        for state in [
+            NonceState {
+                last_used: Some(i128::MAX),
+                chain: None,
+            },
+            NonceState {
+                last_used: None,
+                chain: Some(i128::MAX),
+            },
+        ] {
+            assert!(matches!(state.next(), Err(WalletError::InvalidNonce(_))));
+        }
+    }
+
+    #[test]
+    fn allocate_never_repeats_a_nonce() {
+        let dir = temp_dir();
+        let first = allocate(&dir, SIGNER, None).unwrap();
+        let second = allocate(&dir, SIGNER, None).unwrap();
+        assert!(second > first);
+        assert_eq!(state(&dir, SIGNER).unwrap().last_used, Some(second));
+    }
+
+    #[test]
+    fn allocate_uses_an_explicit_nonce_and_stays_above_it() {
+        let dir = temp_dir();
+        let explicit = now() + HOUR;
+        assert_eq!(allocate(&dir, SIGNER, Some(explicit)).unwrap(), explicit);
+        assert_eq!(allocate(&dir, SIGNER, None).unwrap(), explicit + 1);
+        // Replacing an earlier pending transaction does not lower last_used.
+        allocate(&dir, SIGNER, Some(explicit - 10)).unwrap();
+        assert_eq!(state(&dir, SIGNER).unwrap().last_used, Some(explicit + 1));
+    }
+
+    #[test]
+    fn allocate_rejects_bad_explicit_nonces() {
+        let dir = temp_dir();
+        let chain = now();
+        record_chain(&dir, SIGNER, chain).unwrap();
+        for bad in [0, -1, chain - 1, chain, now() + MAX_NONCE_AHEAD + HOUR] {
+            assert!(
+                matches!(allocate(&dir, SIGNER, Some(bad)), Err(WalletError::InvalidNonce(_))),
+                "nonce {} was accepted",
+                bad
+            );
+        }
+        assert_eq!(state(&dir, SIGNER).unwrap().last_used, None);
+        assert!(allocate(&dir, SIGNER, Some(chain + 1)).is_ok());
+    }
+
+    #[test]
+    fn record_chain_rejects_implausible_nonces() {
+        let dir = temp_dir();
+        for bad in [-1, now() + MAX_NONCE_AHEAD + HOUR, i128::MAX] {
+            let err = record_chain(&dir, SIGNER, bad).unwrap_err();
+            assert_eq!(err.code(), "invalid_nonce");
+        }
+        assert_eq!(state(&dir, SIGNER).unwrap().chain, None);
+
+        let chain = now() + HOUR;
+        let recorded = record_chain(&dir, SIGNER, chain).unwrap();
+        assert_eq!(recorded.chain, Some(chain));
+        assert_eq!(allocate(&dir, SIGNER, None).unwrap(), chain + 1);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::amadeus_tx::{SignedTx, Transaction};
+use crate::wallet::error::WalletError;
+use crate::wallet::storage;
+use std::path::Path;
+
+const UNSIGNED_FORMAT: &str = "amadeus-unsigned-tx";
+const SIGNED_FORMAT: &str = "amadeus-signed-tx";
+const TX_FILE_VERSION: u32 = 1;
+
+/// A transaction carried between an online and an air-gapped machine. `tx` is the packed vecpak
+/// `tx` (signer, nonce, action) for an unsigned file, or the packed `TxU` for a signed one, in
+/// Base58. `hash` is repeated in the clear so both screens can show it for comparison; it is
+/// recomputed and checked on every read.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct TxFile {
+    format: String,
+    version: u32,
+    tx: String,
+    hash: String,
+}
+
+/// Writes `tx` for signing elsewhere. Refuses to overwrite an existing file.
+pub fn write_unsigned(tx: &Transaction, path: &Path) -> Result<(), WalletError> {
+    write(UNSIGNED_FORMAT, &tx.encode(), &tx.hash(), path)
+}
+
+pub fn read_unsigned(path: &Path) -> Result<Transaction, WalletError> {
+    let (packed, hash) = read(UNSIGNED_FORMAT, path)?;
+    let tx = Transaction::decode(&packed)?;
+    if tx.hash() != hash {
+        return Err(WalletError::CorruptFile("hash does not match the transaction".into()));
+    }
+    Ok(tx)
+}
+
+/// Writes a signed transaction for broadcasting elsewhere. Refuses to overwrite an existing file.
+pub fn write_signed(signed: &SignedTx, path: &Path) -> Result<(), WalletError> {
+    write(SIGNED_FORMAT, &signed.encode(), &signed.hash, path)
+}
+
+/// Reads a signed transaction file and checks its hash and signature.
+pub fn read_signed(path: &Path) -> Result<SignedTx, WalletError> {
+    let (packed, hash) = read(SIGNED_FORMAT, path)?;
+    let signed = SignedTx::decode(&packed)?;
+    if signed.hash != hash {
+        return Err(WalletError::CorruptFile("hash does not match the transaction".into()));
+    }
+    signed.verify()?;
+    Ok(signed)
+}
+
+fn write(format: &str, packed: &[u8], hash: &[u8; 32], path: &Path) -> Result<(), WalletError> {
+    if path.exists() {
+        return Err(WalletError::Storage(format!("{} already exists", path.display())));
+    }
+    let file = TxFile {
+        format: format.to_string(),
+        version: TX_FILE_VERSION,
+        tx: bs58::encode(packed).into_string(),
+        hash: bs58::encode(hash).into_string(),
+    };
+    let data = serde_json::to_vec_pretty(&file).map_err(|e| WalletError::Storage(e.to_string()))?;
+    storage::write_atomic(path, &data)
+}
+
+fn read(format: &str, path: &Path) -> Result<(Vec<u8>, [u8; 32]), WalletError> {
+    let data = std::fs::read(path).map_err(|e| WalletError::Storage(e.to_string()))?;
+    let file: TxFile = serde_json::from_slice(&data).map_err(|e| WalletError::CorruptFile(e.to_string()))?;
+    if file.format != format {
+        let expected = if format == UNSIGNED_FORMAT { "an unsigned" } else { "a signed" };
+        return Err(WalletError::CorruptFile(format!("Not {} transaction file", expected)));
+    }
+    if file.version > TX_FILE_VERSION {
+        return Err(WalletError::CorruptFile(format!(
+            "Transaction file version {} is newer than this app supports",
+            file.version
+        )));
+    }
+    let packed = bs58::decode(file.tx.trim())
+        .into_vec()
+        .map_err(|e| WalletError::CorruptFile(e.to_string()))?;
+    let hash = bs58::decode(file.hash.trim())
+        .into_vec()
+        .ok()
+        .and_then(|h| h.try_into().ok())
+        .ok_or_else(|| WalletError::CorruptFile("hash must be 32 bytes of Base58".into()))?;
+    Ok((packed, hash))
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use zeroize::Zeroizing;
+
+pub const PRIVATE_KEY_LEN: usize = 64;
+
+/// Secret text such as a Base58 private key. The buffer is wiped on drop and `Debug` never shows
+/// it; call `expose` only where the plaintext is really needed.
+#[derive(Clone)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        SecretString(Zeroizing::new(value))
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Lets commands take secrets straight off IPC without an intermediate plain `String`.
+impl<'de> serde::Deserialize<'de> for SecretString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SecretString::new)
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(<redacted>)")
+    }
+}
+
+/// A decoded 64-byte private key, wiped on drop. Boxed so moving it never leaves copies on the stack.
+#[derive(Clone)]
+pub struct PrivateKey(Box<Zeroizing<[u8; PRIVATE_KEY_LEN]>>);
+
+impl PrivateKey {
+    /// Copies `bytes` straight into the wiped buffer; `None` unless it is exactly 64 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != PRIVATE_KEY_LEN {
+            return None;
+        }
+        let mut key = Box::new(Zeroizing::new([0u8; PRIVATE_KEY_LEN]));
+        key.copy_from_slice(bytes);
+        Some(PrivateKey(key))
+    }
+
+    pub fn expose(&self) -> &[u8; PRIVATE_KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
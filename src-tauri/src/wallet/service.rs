@@ -1,11 +1,27 @@
+use crate::amadeus_tx::{Attachment, ContractArg, SignedTx, Transaction};
 use crate::wallet::error::WalletError;
 use crate::wallet::keys;
-use crate::wallet::storage;
-use bs58;
-use std::path::PathBuf;
+use crate::network;
+use crate::wallet::backup::{self, BackupMetadata, BackupSettings, RestoredBackup};
+use crate::wallet::keystore::{Account, AccountList, Derivation, Keystore};
+use crate::wallet::message::{self, MessageInput, SignedMessage};
+use crate::wallet::mnemonic;
+use crate::wallet::multisig::{self, Cosigner, MultisigStatus, PartialTx};
+use crate::wallet::nonce::{self, NonceState};
+use crate::wallet::offline;
+use crate::wallet::secret::{PrivateKey, SecretString};
+use crate::wallet::shamir::{self, ShareEncoding};
+use crate::wallet::signer::{KeystoreSigner, Signer};
+use crate::wallet::session::{Session, SessionStatus};
+use crate::wallet::storage::{self, KdfCost, KdfPreset};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::Manager;
 
-const WALLET_FILENAME: &str = "wallet.enc";
+/// Consecutive unused child accounts after which a restore scan stops.
+const DEFAULT_GAP_LIMIT: u32 = 5;
+/// Hard stop for a restore scan, whatever the gap limit.
+const MAX_SCAN_INDEX: u32 = 1000;
 
 pub fn wallet_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, WalletError> {
     let dir = app_handle
@@ -15,77 +31,717 @@ pub fn wallet_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, WalletError>
     Ok(dir.join("wallet"))
 }
 
-fn wallet_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, WalletError> {
-    Ok(wallet_dir(app_handle)?.join(WALLET_FILENAME))
+fn load_keystore(app_handle: &tauri::AppHandle) -> Result<Keystore, WalletError> {
+    Keystore::load(&wallet_dir(app_handle)?)
 }
 
-pub fn create_wallet(app_handle: &tauri::AppHandle, password: &str) -> Result<String, WalletError> {
+fn session(app_handle: &tauri::AppHandle) -> tauri::State<'_, Session> {
+    app_handle.state::<Session>()
+}
+
+fn http(app_handle: &tauri::AppHandle) -> tauri::State<'_, network::HttpClient> {
+    app_handle.state::<network::HttpClient>()
+}
+
+/// The selected account: decrypted with `password` if given, otherwise taken from the unlocked
+/// session.
+fn signer(app_handle: &tauri::AppHandle, password: Option<&str>) -> Result<KeystoreSigner, WalletError> {
+    let keystore = load_keystore(app_handle)?;
+    let account = keystore.selected()?;
+    match password {
+        Some(password) => KeystoreSigner::unlock(&keystore, &account.id, password),
+        None => KeystoreSigner::from_session(&session(app_handle), &account.id),
+    }
+}
+
+/// Adds a freshly generated key as a new account, selects it and returns the private key for backup.
+pub fn create_wallet(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    label: Option<&str>,
+) -> Result<SecretString, WalletError> {
     let private_key_base58 = keys::generate_private_key_base58()?;
-    let path = wallet_path(app_handle)?;
-    storage::encrypt_and_store(&private_key_base58, password, &path)?;
+    add_account(app_handle, &private_key_base58, password, label, false)?;
     Ok(private_key_base58)
 }
 
+/// Adds an existing key as a new account and selects it.
+///
+/// If the key is already in the wallet this fails with `WalletError::WalletExists` unless `force`
+/// is set, in which case the old key file is moved to a timestamped backup (returned) and the
+/// account is re-encrypted under `password`.
 pub fn import_wallet(
     app_handle: &tauri::AppHandle,
     private_key_base58: &str,
     password: &str,
-) -> Result<(), WalletError> {
+    label: Option<&str>,
+    force: bool,
+) -> Result<Option<PathBuf>, WalletError> {
     let normalized = keys::parse_private_key_base58(private_key_base58)?;
-    let path = wallet_path(app_handle)?;
-    storage::encrypt_and_store(&normalized, password, &path)?;
-    Ok(())
+    add_account(app_handle, &normalized, password, label, force)
+}
+
+/// Adds the key behind a recovery phrase from `export_mnemonic`, like `import_wallet`.
+pub fn import_mnemonic(
+    app_handle: &tauri::AppHandle,
+    phrase: &str,
+    passphrase: &str,
+    password: &str,
+    label: Option<&str>,
+    force: bool,
+) -> Result<Option<PathBuf>, WalletError> {
+    let sk = mnemonic::from_mnemonic(phrase, passphrase)?;
+    add_account(app_handle, &keys::encode_private_key_base58(&sk), password, label, force)
+}
+
+fn add_account(
+    app_handle: &tauri::AppHandle,
+    private_key_base58: &SecretString,
+    password: &str,
+    label: Option<&str>,
+    force: bool,
+) -> Result<Option<PathBuf>, WalletError> {
+    let public_key = keys::public_key_from_private_base58(private_key_base58.expose())?;
+    let mut keystore = load_keystore(app_handle)?;
+
+    if let Some(existing) = find_existing_account(&mut keystore, &public_key, password)? {
+        if !force {
+            return Err(WalletError::WalletExists(existing.label));
+        }
+        let path = keystore.key_path(&existing.id);
+        // Keep the cost the key was protected with; only a missing file falls back to the default.
+        let (cost, backup) = if path.exists() {
+            (storage::kdf_cost(&path)?, Some(storage::backup_file(&path, &keystore.backup_dir())?))
+        } else {
+            (KdfCost::default(), None)
+        };
+        storage::encrypt_and_store(private_key_base58, password, &path, cost)?;
+        if let Some(label) = label.filter(|l| !l.trim().is_empty()) {
+            keystore.rename(&existing.id, label)?;
+        }
+        keystore.select(&existing.id)?;
+        keystore.save()?;
+        return Ok(backup);
+    }
+
+    let account = keystore.add(label, public_key);
+    let path = keystore.key_path(&account.id);
+    if path.exists() {
+        return Err(WalletError::WalletExists(account.label));
+    }
+    storage::encrypt_and_store(private_key_base58, password, &path, KdfCost::default())?;
+    keystore.save()?;
+    Ok(None)
+}
+
+/// The account holding `public_key`. An account adopted from the legacy `wallet.enc` has no cached
+/// public key until it is unlocked, so its key file is checked instead: a current-format file
+/// records the key, and a legacy one is decrypted with `password`, which a re-import usually
+/// shares. Keys found this way are cached in `keystore`.
+fn find_existing_account(
+    keystore: &mut Keystore,
+    public_key: &str,
+    password: &str,
+) -> Result<Option<Account>, WalletError> {
+    if let Some(account) = keystore.find_by_public_key(public_key) {
+        return Ok(Some(account.clone()));
+    }
+    let uncached: Vec<String> = keystore
+        .list()
+        .accounts
+        .into_iter()
+        .filter(|a| a.public_key.is_none())
+        .map(|a| a.id)
+        .collect();
+    for id in uncached {
+        let path = keystore.key_path(&id);
+        let Ok(data) = std::fs::read(&path) else {
+            continue;
+        };
+        let recorded = match storage::inspect_key_file(&data) {
+            Ok(Some(recorded)) => Some(recorded),
+            Ok(None) => storage::load_and_decrypt(password, &path)
+                .ok()
+                .and_then(|sk| keys::public_key_from_private_base58(sk.expose()).ok()),
+            Err(_) => None,
+        };
+        let Some(recorded) = recorded else {
+            continue;
+        };
+        keystore.set_public_key(&id, recorded.clone())?;
+        if recorded == public_key {
+            return Ok(Some(keystore.get(&id)?.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Adds child `index` of the selected account (by default the next one not yet in the wallet) as a
+/// new account encrypted under the same password, and selects it.
+pub fn derive_account(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    index: Option<u32>,
+    label: Option<&str>,
+) -> Result<Account, WalletError> {
+    let mut keystore = load_keystore(app_handle)?;
+    let (root, root_key) = load_root_key(&keystore, password)?;
+    let index = index.unwrap_or_else(|| keystore.next_child_index(&root));
+    let child = keys::derive_child_key(&root_key, index)?;
+    add_derived_account(&mut keystore, &root, index, &child, password, label)
+}
+
+/// Re-adds the children of the selected account that have a balance or transactions on chain,
+/// walking indexes from 0 until `gap_limit` unused ones in a row. Children already in the wallet
+/// count as used. Returns the accounts added; the root stays selected.
+pub async fn scan_derived_accounts(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    api_url: &str,
+    gap_limit: Option<u32>,
+) -> Result<Vec<Account>, WalletError> {
+    let mut keystore = load_keystore(app_handle)?;
+    let (root, root_key) = load_root_key(&keystore, password)?;
+    let gap_limit = gap_limit.unwrap_or(DEFAULT_GAP_LIMIT).max(1);
+    let mut added = Vec::new();
+    let mut unused = 0;
+    let mut index = 0;
+    while unused < gap_limit && index < MAX_SCAN_INDEX {
+        let child = keys::derive_child_key(&root_key, index)?;
+        let public_key = bs58::encode(keys::public_key_bytes(&child)).into_string();
+        if keystore.find_by_public_key(&public_key).is_some() {
+            unused = 0;
+        } else if has_activity(&http(app_handle), api_url, &public_key).await? {
+            added.push(add_derived_account(&mut keystore, &root, index, &child, password, None)?);
+            unused = 0;
+        } else {
+            unused += 1;
+        }
+        index += 1;
+    }
+    keystore.select(&root)?;
+    keystore.save()?;
+    Ok(added)
+}
+
+/// The selected account's key, which must not itself be derived.
+fn load_root_key(keystore: &Keystore, password: &str) -> Result<(String, PrivateKey), WalletError> {
+    let root = keystore.selected()?;
+    if root.derivation.is_some() {
+        return Err(WalletError::InvalidSetting(
+            "Select the account the others were derived from, not a derived one".into(),
+        ));
+    }
+    let private_key_base58 = storage::load_and_decrypt(password, &keystore.key_path(&root.id))?;
+    Ok((root.id.clone(), keys::decode_private_key_base58(private_key_base58.expose())?))
+}
+
+fn add_derived_account(
+    keystore: &mut Keystore,
+    root: &str,
+    index: u32,
+    child: &PrivateKey,
+    password: &str,
+    label: Option<&str>,
+) -> Result<Account, WalletError> {
+    let public_key = bs58::encode(keys::public_key_bytes(child)).into_string();
+    if let Some(existing) = keystore.find_by_public_key(&public_key) {
+        return Err(WalletError::WalletExists(existing.label.clone()));
+    }
+    let cost = storage::kdf_cost(&keystore.key_path(root))?;
+    let derivation = Derivation {
+        root: root.to_string(),
+        index,
+    };
+    let account = keystore.add_derived(label, public_key, derivation)?;
+    storage::encrypt_and_store(
+        &keys::encode_private_key_base58(child),
+        password,
+        &keystore.key_path(&account.id),
+        cost,
+    )?;
+    keystore.save()?;
+    Ok(account)
 }
 
+/// Whether an address has a non-zero balance or any transactions. A failed lookup is an error
+/// rather than "unused", so a flaky node cannot cut a restore short.
+async fn has_activity(http: &network::HttpClient, api_url: &str, address: &str) -> Result<bool, WalletError> {
+    let balance = network::get_balance_from_api(http, api_url, address).await?;
+    if let Some(error) = balance.error {
+        return Err(error.into());
+    }
+    if let Some(flat) = balance.balance_flat.as_deref() {
+        let flat: u128 = flat.trim().parse().map_err(|_| {
+            network::NetworkError::Decode(format!("Balance '{}' is not a whole number of flat units", flat))
+        })?;
+        if flat > 0 {
+            return Ok(true);
+        }
+    }
+    let txs = network::get_transactions_from_api(http, api_url, address, None, None).await?;
+    if let Some(error) = txs.error {
+        return Err(error.into());
+    }
+    Ok(!txs.transactions.is_empty())
+}
+
+/// Unlocks the selected account, starting a session that can sign without the password until it
+/// is locked or times out, and returns its public key.
 pub fn unlock_wallet(app_handle: &tauri::AppHandle, password: &str) -> Result<String, WalletError> {
-    let path = wallet_path(app_handle)?;
-    if !path.exists() {
-        return Err(WalletError::Storage("No wallet found. Create or import one first.".into()));
+    let mut keystore = load_keystore(app_handle)?;
+    let account = keystore.selected()?.clone();
+    let private_key_base58 = storage::load_and_decrypt(password, &keystore.key_path(&account.id))?;
+    let public_key = keys::public_key_from_private_base58(private_key_base58.expose())?;
+    if account.public_key.as_deref() != Some(public_key.as_str()) {
+        keystore.set_public_key(&account.id, public_key.clone())?;
+        keystore.save()?;
     }
-    let private_key_base58 = storage::load_and_decrypt(password, &path)?;
-    keys::public_key_from_private_base58(&private_key_base58)
+    session(app_handle).start(&account.id, &public_key, private_key_base58);
+    Ok(public_key)
+}
+
+pub fn lock_wallet(app_handle: &tauri::AppHandle) {
+    session(app_handle).lock();
+}
+
+pub fn session_status(app_handle: &tauri::AppHandle) -> SessionStatus {
+    session(app_handle).status()
+}
+
+pub fn set_auto_lock_timeout(app_handle: &tauri::AppHandle, timeout_secs: u64) -> Result<(), WalletError> {
+    session(app_handle).set_idle_timeout(Duration::from_secs(timeout_secs))
 }
 
 pub fn has_wallet(app_handle: &tauri::AppHandle) -> Result<bool, WalletError> {
-    Ok(wallet_path(app_handle)?.exists())
+    Ok(!load_keystore(app_handle)?.is_empty())
 }
 
+/// Path of the selected account's encrypted key file.
 pub fn wallet_file_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, WalletError> {
-    wallet_path(app_handle)
+    let keystore = load_keystore(app_handle)?;
+    Ok(keystore.key_path(&keystore.selected()?.id))
 }
 
+pub fn list_accounts(app_handle: &tauri::AppHandle) -> Result<AccountList, WalletError> {
+    Ok(load_keystore(app_handle)?.list())
+}
+
+pub fn rename_account(app_handle: &tauri::AppHandle, id: &str, label: &str) -> Result<(), WalletError> {
+    let mut keystore = load_keystore(app_handle)?;
+    keystore.rename(id, label)?;
+    keystore.save()
+}
+
+/// Switching accounts locks the session of the previously selected one.
+pub fn select_account(app_handle: &tauri::AppHandle, id: &str) -> Result<(), WalletError> {
+    let mut keystore = load_keystore(app_handle)?;
+    let previous = keystore.selected().ok().map(|a| a.id.clone());
+    keystore.select(id)?;
+    keystore.save()?;
+    if let Some(previous) = previous.filter(|p| p != id) {
+        session(app_handle).lock_account(&previous);
+    }
+    Ok(())
+}
+
+/// Removes an account, moving its key file to a timestamped backup (returned). The account's
+/// password is required so a stray click cannot remove a key.
+pub fn delete_account(
+    app_handle: &tauri::AppHandle,
+    id: &str,
+    password: &str,
+) -> Result<Option<PathBuf>, WalletError> {
+    let mut keystore = load_keystore(app_handle)?;
+    keystore.get(id)?;
+    storage::load_and_decrypt(password, &keystore.key_path(id))?;
+    let backup = keystore.remove(id)?;
+    keystore.save()?;
+    session(app_handle).lock_account(id);
+    Ok(backup)
+}
+
+/// The selected account's key as a recovery phrase. Always asks for the password, even when
+/// unlocked, since the words are the key.
+pub fn export_mnemonic(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    passphrase: &str,
+) -> Result<SecretString, WalletError> {
+    let keystore = load_keystore(app_handle)?;
+    let path = keystore.key_path(&keystore.selected()?.id);
+    let private_key_base58 = storage::load_and_decrypt(password, &path)?;
+    mnemonic::to_mnemonic(&keys::decode_private_key_base58(private_key_base58.expose())?, passphrase)
+}
+
+/// Splits the selected account's key into `shares` shares, any `threshold` of which restore it with
+/// `recover_from_shares`. Always asks for the password, like `export_mnemonic`.
+pub fn split_key(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    threshold: u8,
+    shares: u8,
+    encoding: ShareEncoding,
+) -> Result<Vec<SecretString>, WalletError> {
+    let keystore = load_keystore(app_handle)?;
+    let path = keystore.key_path(&keystore.selected()?.id);
+    let private_key_base58 = storage::load_and_decrypt(password, &path)?;
+    shamir::split(&keys::decode_private_key_base58(private_key_base58.expose())?, threshold, shares, encoding)
+}
+
+/// Combines key shares and imports the result through `import_wallet`.
+pub fn recover_from_shares(
+    app_handle: &tauri::AppHandle,
+    shares: &[&str],
+    password: &str,
+    label: Option<&str>,
+    force: bool,
+) -> Result<Option<PathBuf>, WalletError> {
+    let sk = shamir::combine(shares)?;
+    import_wallet(app_handle, keys::encode_private_key_base58(&sk).expose(), password, label, force)
+}
+
+/// Writes all accounts and the given frontend settings to an encrypted bundle at `path`.
+pub fn export_backup(
+    app_handle: &tauri::AppHandle,
+    password: &str,
+    settings: BackupSettings,
+    path: &Path,
+) -> Result<BackupMetadata, WalletError> {
+    backup::export(&load_keystore(app_handle)?, password, settings, path)
+}
+
+/// Restores the accounts in a bundle from `export_backup` that are not already in the wallet.
+pub fn import_backup(app_handle: &tauri::AppHandle, password: &str, path: &Path) -> Result<RestoredBackup, WalletError> {
+    backup::import(&mut load_keystore(app_handle)?, password, path)
+}
+
+/// Re-encrypts the selected account's key under a new password, keeping its KDF cost.
 pub fn change_password(
     app_handle: &tauri::AppHandle,
     current_password: &str,
     new_password: &str,
 ) -> Result<(), WalletError> {
-    let path = wallet_path(app_handle)?;
-    if !path.exists() {
-        return Err(WalletError::Storage("No wallet found.".into()));
-    }
+    let keystore = load_keystore(app_handle)?;
+    let path = keystore.key_path(&keystore.selected()?.id);
     let private_key_base58 = storage::load_and_decrypt(current_password, &path)?;
-    storage::encrypt_and_store(&private_key_base58, new_password, &path)?;
+    let cost = storage::kdf_cost(&path)?;
+    storage::encrypt_and_store(&private_key_base58, new_password, &path, cost)?;
     Ok(())
 }
 
-pub fn sign_transaction(
+/// KDF cost of the selected account's key file.
+pub fn kdf_cost(app_handle: &tauri::AppHandle) -> Result<KdfCost, WalletError> {
+    let keystore = load_keystore(app_handle)?;
+    storage::kdf_cost(&keystore.key_path(&keystore.selected()?.id))
+}
+
+/// Re-encrypts the selected account's key with the preset's KDF cost.
+pub fn set_kdf_preset(
     app_handle: &tauri::AppHandle,
     password: &str,
+    preset: KdfPreset,
+) -> Result<(), WalletError> {
+    let keystore = load_keystore(app_handle)?;
+    let path = keystore.key_path(&keystore.selected()?.id);
+    let private_key_base58 = storage::load_and_decrypt(password, &path)?;
+    storage::encrypt_and_store(&private_key_base58, password, &path, preset.cost())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct KdfBenchmark {
+    pub preset: KdfPreset,
+    #[serde(flatten)]
+    pub cost: KdfCost,
+    /// How long one unlock takes at this cost on this machine.
+    pub millis: u64,
+}
+
+pub fn benchmark_kdf(preset: KdfPreset) -> Result<KdfBenchmark, WalletError> {
+    let cost = preset.cost();
+    let elapsed = storage::benchmark_kdf(cost)?;
+    Ok(KdfBenchmark {
+        preset,
+        cost,
+        millis: elapsed.as_millis() as u64,
+    })
+}
+
+/// Nonce bookkeeping for one address, for the frontend; nonces are decimal strings.
+#[derive(Debug, serde::Serialize)]
+pub struct NonceInfo {
+    pub address: String,
+    pub last_used: Option<String>,
+    pub chain: Option<String>,
+    /// What the next transaction will use unless a nonce is given.
+    pub next: String,
+}
+
+impl NonceInfo {
+    fn new(address: &str, state: NonceState) -> Result<Self, WalletError> {
+        Ok(NonceInfo {
+            address: address.to_string(),
+            last_used: state.last_used.map(|n| n.to_string()),
+            chain: state.chain.map(|n| n.to_string()),
+            next: state.next()?.to_string(),
+        })
+    }
+}
+
+/// Reserves the nonce for a transaction from `signer`; `explicit` is a decimal nonce to reuse,
+/// e.g. to replace a pending transaction.
+fn allocate_nonce(
+    app_handle: &tauri::AppHandle,
+    signer: &[u8; 48],
+    explicit: Option<&str>,
+) -> Result<i128, WalletError> {
+    let explicit = explicit
+        .map(|n| {
+            n.trim()
+                .parse::<i128>()
+                .map_err(|_| WalletError::InvalidNonce(format!("not a number: {}", n)))
+        })
+        .transpose()?;
+    nonce::allocate(&wallet_dir(app_handle)?, &bs58::encode(signer).into_string(), explicit)
+}
+
+pub fn nonce_status(app_handle: &tauri::AppHandle, address_base58: &str) -> Result<NonceInfo, WalletError> {
+    let address = bs58::encode(keys::decode_public_key_base58(address_base58)?).into_string();
+    NonceInfo::new(&address, nonce::state(&wallet_dir(app_handle)?, &address)?)
+}
+
+/// Fetches the node's nonce for `address_base58` so later transactions are numbered above it.
+pub async fn sync_nonce(
+    app_handle: &tauri::AppHandle,
+    api_url: &str,
+    address_base58: &str,
+) -> Result<NonceInfo, WalletError> {
+    let address = bs58::encode(keys::decode_public_key_base58(address_base58)?).into_string();
+    let result = network::get_account_nonce_from_api(&http(app_handle), api_url, &address).await?;
+    if let Some(error) = result.error {
+        return Err(error.into());
+    }
+    let dir = wallet_dir(app_handle)?;
+    let state = match result.nonce {
+        Some(chain) => {
+            let chain = chain
+                .parse::<i128>()
+                .map_err(|_| network::NetworkError::Decode(format!("invalid nonce {}", chain)))?;
+            nonce::record_chain(&dir, &address, chain)?
+        }
+        None => nonce::state(&dir, &address)?,
+    };
+    NonceInfo::new(&address, state)
+}
+
+/// Signs with the unlocked session, or with `password` if one is given. Without an explicit
+/// `nonce` the next one for the account is reserved.
+pub fn sign_transaction(
+    app_handle: &tauri::AppHandle,
+    password: Option<&str>,
     recipient_base58: &str,
     amount: &str,
     memo: &str,
+    nonce: Option<&str>,
 ) -> Result<String, WalletError> {
-    let path = wallet_path(app_handle)?;
-    if !path.exists() {
-        return Err(WalletError::Storage("No wallet found.".into()));
-    }
-    let private_key_base58 = storage::load_and_decrypt(password, &path)?;
+    let signer = signer(app_handle, password)?;
+    let nonce = allocate_nonce(app_handle, &signer.public_key(), nonce)?;
     let symbol = if memo.is_empty() { "AMA" } else { memo.trim() };
     let tx_packed = crate::amadeus_tx::build_coin_transfer(
-        &private_key_base58,
+        &signer,
+        nonce,
         recipient_base58.trim(),
         amount.trim(),
         symbol,
     )?;
     Ok(bs58::encode(&tx_packed).into_string())
 }
+
+pub fn sign_contract_call(
+    app_handle: &tauri::AppHandle,
+    password: Option<&str>,
+    contract: &str,
+    function: &str,
+    args: &[ContractArg],
+    attachment: Option<&Attachment>,
+    nonce: Option<&str>,
+) -> Result<String, WalletError> {
+    let signer = signer(app_handle, password)?;
+    let nonce = allocate_nonce(app_handle, &signer.public_key(), nonce)?;
+    let tx_packed = crate::amadeus_tx::build_contract_call(
+        &signer,
+        nonce,
+        contract,
+        function,
+        args,
+        attachment,
+    )?;
+    Ok(bs58::encode(&tx_packed).into_string())
+}
+
+/// The unsigned transfer `sign_transaction` would produce, so the user can review it first. The
+/// nonce shown is the next one, but it is not reserved.
+pub fn preview_transaction(
+    app_handle: &tauri::AppHandle,
+    public_key_base58: &str,
+    recipient_base58: &str,
+    amount: &str,
+    memo: &str,
+) -> Result<Transaction, WalletError> {
+    let signer = keys::decode_public_key_base58(public_key_base58)?;
+    let next = nonce::state(&wallet_dir(app_handle)?, &bs58::encode(signer).into_string())?.next()?;
+    transfer_transaction(signer, next, recipient_base58, amount, memo)
+}
+
+fn transfer_transaction(
+    signer: [u8; 48],
+    nonce: i128,
+    recipient_base58: &str,
+    amount: &str,
+    memo: &str,
+) -> Result<Transaction, WalletError> {
+    let symbol = if memo.is_empty() { "AMA" } else { memo.trim() };
+    crate::amadeus_tx::coin_transfer_transaction(signer, nonce, recipient_base58.trim(), amount.trim(), symbol)
+}
+
+/// Writes a transfer to `path` for signing on an offline machine, reserving its nonce here.
+pub fn export_unsigned_transfer(
+    app_handle: &tauri::AppHandle,
+    public_key_base58: &str,
+    recipient_base58: &str,
+    amount: &str,
+    memo: &str,
+    nonce: Option<&str>,
+    path: &Path,
+) -> Result<Transaction, WalletError> {
+    let signer = keys::decode_public_key_base58(public_key_base58)?;
+    let nonce = allocate_nonce(app_handle, &signer, nonce)?;
+    let tx = transfer_transaction(signer, nonce, recipient_base58, amount, memo)?;
+    offline::write_unsigned(&tx, path)?;
+    Ok(tx)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn export_unsigned_contract_call(
+    app_handle: &tauri::AppHandle,
+    public_key_base58: &str,
+    contract: &str,
+    function: &str,
+    args: &[ContractArg],
+    attachment: Option<&Attachment>,
+    nonce: Option<&str>,
+    path: &Path,
+) -> Result<Transaction, WalletError> {
+    let signer = keys::decode_public_key_base58(public_key_base58)?;
+    let nonce = allocate_nonce(app_handle, &signer, nonce)?;
+    let tx = crate::amadeus_tx::contract_call_transaction(signer, nonce, contract, function, args, attachment)?;
+    offline::write_unsigned(&tx, path)?;
+    Ok(tx)
+}
+
+/// Reads an unsigned transaction file so it can be reviewed before signing.
+pub fn inspect_unsigned_transaction(path: &Path) -> Result<Transaction, WalletError> {
+    offline::read_unsigned(path)
+}
+
+/// Signs the transaction in `input` with the selected account, which must be its signer, and
+/// writes the result to `output`.
+pub fn sign_transaction_file(
+    app_handle: &tauri::AppHandle,
+    password: Option<&str>,
+    input: &Path,
+    output: &Path,
+) -> Result<SignedTx, WalletError> {
+    let tx = offline::read_unsigned(input)?;
+    let signer = signer(app_handle, password)?;
+    if signer.public_key() != tx.signer {
+        return Err(WalletError::InvalidTransaction(
+            "the transaction was built for a different account".into(),
+        ));
+    }
+    let signed = tx.sign(&signer)?;
+    offline::write_signed(&signed, output)?;
+    Ok(signed)
+}
+
+/// Reads and verifies a signed transaction file; returns it Base58-encoded for submission.
+pub fn load_signed_transaction_file(path: &Path) -> Result<String, WalletError> {
+    Ok(bs58::encode(offline::read_signed(path)?.encode()).into_string())
+}
+
+/// The selected account's public key and proof of possession, to share with the other co-signers.
+pub fn multisig_proof(app_handle: &tauri::AppHandle, password: Option<&str>) -> Result<Cosigner, WalletError> {
+    multisig::prove(&signer(app_handle, password)?)
+}
+
+/// The shared account address of a set of co-signers.
+pub fn multisig_address(cosigners: &[Cosigner]) -> Result<String, WalletError> {
+    multisig::address(cosigners)
+}
+
+/// Starts a transfer from the co-signers' shared account; nothing is signed yet.
+pub fn create_multisig_transfer(
+    app_handle: &tauri::AppHandle,
+    cosigners: &[Cosigner],
+    recipient_base58: &str,
+    amount: &str,
+    memo: &str,
+    nonce: Option<&str>,
+) -> Result<PartialTx, WalletError> {
+    let signer = keys::decode_public_key_base58(&multisig::address(cosigners)?)?;
+    let nonce = allocate_nonce(app_handle, &signer, nonce)?;
+    let tx = transfer_transaction(signer, nonce, recipient_base58, amount, memo)?;
+    multisig::create(&tx, cosigners)
+}
+
+/// Adds the selected account's signature, using the unlocked session when no password is given.
+pub fn sign_multisig(
+    app_handle: &tauri::AppHandle,
+    password: Option<&str>,
+    partial: &PartialTx,
+) -> Result<PartialTx, WalletError> {
+    let signer = signer(app_handle, password)?;
+    multisig::sign(partial, &signer)
+}
+
+pub fn merge_multisig(partials: &[PartialTx]) -> Result<PartialTx, WalletError> {
+    multisig::merge(partials)
+}
+
+pub fn multisig_status(partial: &PartialTx) -> Result<MultisigStatus, WalletError> {
+    multisig::status(partial)
+}
+
+/// Returns the Base58 signed transaction, ready to submit. Every co-signer must have signed.
+pub fn finalize_multisig(partial: &PartialTx) -> Result<String, WalletError> {
+    Ok(bs58::encode(multisig::finalize(partial)?.encode()).into_string())
+}
+
+/// Signs `message` under the message DST with the selected account, using the unlocked session
+/// when no password is given.
+pub fn sign_message(
+    app_handle: &tauri::AppHandle,
+    password: Option<&str>,
+    message: &MessageInput,
+) -> Result<SignedMessage, WalletError> {
+    let signer = signer(app_handle, password)?;
+    message::sign(&signer, &message.to_bytes()?)
+}
+
+pub fn verify_message(
+    public_key_base58: &str,
+    message: &MessageInput,
+    signature_base58: &str,
+) -> Result<bool, WalletError> {
+    message::verify(public_key_base58, &message.to_bytes()?, signature_base58)
+}
+
+/// Decodes a Base58 signed transaction and checks its hash and signature.
+pub fn verify_signed_transaction(signed_tx_base58: &str) -> Result<SignedTx, WalletError> {
+    let packed = bs58::decode(signed_tx_base58.trim())
+        .into_vec()
+        .map_err(|e| WalletError::InvalidTransaction(e.to_string()))?;
+    let signed = SignedTx::decode(&packed)?;
+    signed.verify()?;
+    Ok(signed)
+}
@@ -0,0 +1,151 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::secret::SecretString;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How often the background task looks for an expired session.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+/// Emitted to the frontend when the session is locked because it sat idle.
+pub const AUTO_LOCKED_EVENT: &str = "wallet://auto-locked";
+
+/// The decrypted key of the unlocked account, kept in Tauri managed state so signing commands do
+/// not need the password. The key is zeroized when the session locks.
+pub struct Session {
+    unlocked: Mutex<Option<Unlocked>>,
+    idle_timeout: Mutex<Duration>,
+}
+
+struct Unlocked {
+    account_id: String,
+    public_key: String,
+    private_key_base58: SecretString,
+    last_used: Instant,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SessionStatus {
+    pub unlocked: bool,
+    pub account_id: Option<String>,
+    pub public_key: Option<String>,
+    pub idle_timeout_secs: u64,
+    /// Seconds until auto-lock if nothing else uses the key.
+    pub locks_in_secs: Option<u64>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            unlocked: Mutex::new(None),
+            idle_timeout: Mutex::new(DEFAULT_IDLE_TIMEOUT),
+        }
+    }
+}
+
+impl Session {
+    pub fn start(&self, account_id: &str, public_key: &str, private_key_base58: SecretString) {
+        *self.lock_state() = Some(Unlocked {
+            account_id: account_id.to_string(),
+            public_key: public_key.to_string(),
+            private_key_base58,
+            last_used: Instant::now(),
+        });
+    }
+
+    pub fn lock(&self) {
+        *self.lock_state() = None;
+    }
+
+    /// Locks only if `account_id` is the unlocked account, e.g. after it is deleted or deselected.
+    pub fn lock_account(&self, account_id: &str) {
+        let mut state = self.lock_state();
+        if state.as_ref().is_some_and(|u| u.account_id == account_id) {
+            *state = None;
+        }
+    }
+
+    /// Runs `f` on the unlocked key for `account_id`, refreshing the idle timer. The key is only
+    /// lent out, so no copy of it outlives the session.
+    pub fn with_private_key<T>(
+        &self,
+        account_id: &str,
+        f: impl FnOnce(&SecretString) -> Result<T, WalletError>,
+    ) -> Result<T, WalletError> {
+        let timeout = self.idle_timeout();
+        let mut state = self.lock_state();
+        Self::expire(&mut state, timeout);
+        match state.as_mut() {
+            Some(u) if u.account_id == account_id => {
+                u.last_used = Instant::now();
+                f(&u.private_key_base58)
+            }
+            _ => Err(WalletError::Locked),
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        let timeout = self.idle_timeout();
+        let mut state = self.lock_state();
+        Self::expire(&mut state, timeout);
+        SessionStatus {
+            unlocked: state.is_some(),
+            account_id: state.as_ref().map(|u| u.account_id.clone()),
+            public_key: state.as_ref().map(|u| u.public_key.clone()),
+            idle_timeout_secs: timeout.as_secs(),
+            locks_in_secs: state
+                .as_ref()
+                .map(|u| timeout.saturating_sub(u.last_used.elapsed()).as_secs()),
+        }
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        *self.idle_timeout.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_idle_timeout(&self, timeout: Duration) -> Result<(), WalletError> {
+        if timeout.is_zero() {
+            return Err(WalletError::InvalidSetting(
+                "Auto-lock timeout must be at least one second".into(),
+            ));
+        }
+        *self.idle_timeout.lock().unwrap_or_else(|e| e.into_inner()) = timeout;
+        Ok(())
+    }
+
+    /// Drops the key if the session has been idle too long; returns whether it did.
+    fn expire_idle(&self) -> bool {
+        let timeout = self.idle_timeout();
+        Self::expire(&mut self.lock_state(), timeout)
+    }
+
+    fn expire(state: &mut Option<Unlocked>, timeout: Duration) -> bool {
+        if state
+            .as_ref()
+            .is_some_and(|u| u.last_used.elapsed() >= timeout)
+        {
+            *state = None;
+            return true;
+        }
+        false
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, Option<Unlocked>> {
+        self.unlocked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Locks idle sessions even when nothing touches the key, so it does not linger in memory. Runs on
+/// Tauri's async runtime and stops with it.
+pub fn spawn_auto_lock(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        sweep.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            sweep.tick().await;
+            if app_handle.state::<Session>().expire_idle() {
+                let _ = app_handle.emit(AUTO_LOCKED_EVENT, ());
+            }
+        }
+    });
+}
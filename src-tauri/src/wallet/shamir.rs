@@ -0,0 +1,263 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::keys;
+use crate::wallet::mnemonic;
+use crate::wallet::secret::{PrivateKey, SecretString, PRIVATE_KEY_LEN};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+const SHARE_VERSION: u8 = 1;
+const HEADER_LEN: usize = 5;
+const CHECKSUM_LEN: usize = 4;
+/// `version | set id (2) | threshold | index | 64 share bytes | first 4 bytes of SHA-256`.
+const SHARE_LEN: usize = HEADER_LEN + PRIVATE_KEY_LEN + CHECKSUM_LEN;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareEncoding {
+    #[default]
+    Base58,
+    /// BIP39 English words, like the recovery phrase.
+    Words,
+}
+
+/// Splits the key into `shares` shares, any `threshold` of which recover it (Shamir over GF(256),
+/// byte by byte). Every share carries the threshold, its index, an id shared by the whole set and
+/// a checksum, so mixed-up or mistyped shares are caught before combining.
+pub fn split(
+    sk: &PrivateKey,
+    threshold: u8,
+    shares: u8,
+    encoding: ShareEncoding,
+) -> Result<Vec<SecretString>, WalletError> {
+    if threshold < 2 || threshold > shares {
+        return Err(WalletError::InvalidShare(format!(
+            "need 2 <= threshold <= shares, got {} of {}",
+            threshold, shares
+        )));
+    }
+    let mut set_id = [0u8; 2];
+    rand::rng().fill_bytes(&mut set_id);
+    // One polynomial per key byte: the byte itself is the constant term, the rest are random.
+    let mut coefficients = Zeroizing::new(vec![0u8; PRIVATE_KEY_LEN * (threshold as usize - 1)]);
+    rand::rng().fill_bytes(&mut coefficients);
+
+    let shares = (1..=shares)
+        .map(|x| {
+            let mut share = Zeroizing::new(Vec::with_capacity(SHARE_LEN));
+            share.extend_from_slice(&[SHARE_VERSION, set_id[0], set_id[1], threshold, x]);
+            for (i, &secret) in sk.expose().iter().enumerate() {
+                let higher = &coefficients[i * (threshold as usize - 1)..(i + 1) * (threshold as usize - 1)];
+                // Horner's rule, highest degree first.
+                let y = higher.iter().rev().fold(0u8, |acc, &c| gf_mul(acc, x) ^ c);
+                share.push(gf_mul(y, x) ^ secret);
+            }
+            let checksum = Sha256::digest(&share[..]);
+            share.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+            match encoding {
+                ShareEncoding::Base58 => SecretString::new(bs58::encode(&share[..]).into_string()),
+                ShareEncoding::Words => mnemonic::encode_words(&share),
+            }
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Recovers the key from at least `threshold` shares of one set, in any order and either encoding.
+pub fn combine(shares: &[&str]) -> Result<PrivateKey, WalletError> {
+    let decoded = shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| decode_share(s).map_err(|e| WalletError::InvalidShare(format!("share {}: {}", i + 1, e))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = decoded
+        .first()
+        .ok_or_else(|| WalletError::InvalidShare("no shares given".into()))?;
+    let (set_id, threshold) = (&first[1..3], first[3]);
+    let mut xs: Vec<u8> = Vec::with_capacity(decoded.len());
+    for share in &decoded {
+        if &share[1..3] != set_id || share[3] != threshold {
+            return Err(WalletError::InvalidShare("shares come from different splits".into()));
+        }
+        if xs.contains(&share[4]) {
+            return Err(WalletError::InvalidShare(format!("share #{} was given twice", share[4])));
+        }
+        xs.push(share[4]);
+    }
+    if decoded.len() < threshold as usize {
+        return Err(WalletError::InvalidShare(format!(
+            "need {} shares, got {}",
+            threshold,
+            decoded.len()
+        )));
+    }
+    let used = &decoded[..threshold as usize];
+    let xs = &xs[..threshold as usize];
+
+    // Lagrange interpolation at x = 0; in GF(256) subtraction is XOR.
+    let weights: Vec<u8> = xs
+        .iter()
+        .map(|&xi| {
+            xs.iter()
+                .filter(|&&xj| xj != xi)
+                .fold(1u8, |acc, &xj| gf_mul(acc, gf_mul(xj, gf_inv(xj ^ xi))))
+        })
+        .collect();
+    let mut key = Zeroizing::new([0u8; PRIVATE_KEY_LEN]);
+    for (byte, out) in key.iter_mut().enumerate() {
+        *out = used
+            .iter()
+            .zip(&weights)
+            .fold(0u8, |acc, (share, &w)| acc ^ gf_mul(share[HEADER_LEN + byte], w));
+    }
+    let sk = PrivateKey::from_slice(&key[..]).ok_or_else(|| WalletError::Crypto("Bad key length".into()))?;
+    keys::validate_private_key(&sk)?;
+    Ok(sk)
+}
+
+/// Word shares contain spaces; Base58 ones never do.
+fn decode_share(s: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let s = s.trim();
+    let share = if s.contains(char::is_whitespace) {
+        mnemonic::decode_words(s, SHARE_LEN).map_err(|e| match e {
+            WalletError::InvalidMnemonic(m) => m,
+            e => e.to_string(),
+        })?
+    } else {
+        Zeroizing::new(bs58::decode(s).into_vec().map_err(|e| e.to_string())?)
+    };
+    if share.len() != SHARE_LEN {
+        return Err(format!("expected {} bytes, got {}", SHARE_LEN, share.len()));
+    }
+    let (body, checksum) = share.split_at(SHARE_LEN - CHECKSUM_LEN);
+    if Sha256::digest(body)[..CHECKSUM_LEN] != *checksum {
+        return Err("checksum mismatch; check for typos".into());
+    }
+    if body[0] != SHARE_VERSION {
+        return Err(format!("unsupported share version {}", body[0]));
+    }
+    if body[3] < 2 || body[4] == 0 {
+        return Err("malformed header".into());
+    }
+    Ok(share)
+}
+
+/// Multiplication in GF(2^8) modulo the AES polynomial x^8 + x^4 + x^3 + x + 1, without
+/// data-dependent branches or tables.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = a >> 7;
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(carry));
+        b >>= 1;
+    }
+    product
+}
+
+/// `a^254`, the multiplicative inverse of a non-zero `a`.
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a4 = gf_mul(a2, a2);
+    let a8 = gf_mul(a4, a4);
+    let a16 = gf_mul(a8, a8);
+    let a32 = gf_mul(a16, a16);
+    let a64 = gf_mul(a32, a32);
+    let a128 = gf_mul(a64, a64);
+    // 254 = 128 + 64 + 32 + 16 + 8 + 4 + 2
+    [a64, a32, a16, a8, a4, a2].iter().fold(a128, |acc, &p| gf_mul(acc, p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> PrivateKey {
+        keys::decode_private_key_base58(keys::generate_private_key_base58().unwrap().expose()).unwrap()
+    }
+
+    fn set_id(share: &SecretString) -> Vec<u8> {
+        bs58::decode(share.expose()).into_vec().unwrap()[1..3].to_vec()
+    }
+
+    /// Every ordering of every `k`-element subset of `0..n`.
+    fn arrangements(n: usize, k: usize) -> Vec<Vec<usize>> {
+        if k == 0 {
+            return vec![Vec::new()];
+        }
+        let mut out = Vec::new();
+        for prefix in arrangements(n, k - 1) {
+            for i in (0..n).filter(|i| !prefix.contains(i)) {
+                out.push([prefix.clone(), vec![i]].concat());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn gf_inv_is_the_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(gf_inv(a), a), 1, "a = {}", a);
+        }
+    }
+
+    #[test]
+    fn any_threshold_subset_recovers_the_key() {
+        let sk = key();
+        let shares = split(&sk, 3, 5, ShareEncoding::Base58).unwrap();
+        for picked in arrangements(5, 3).into_iter().chain(arrangements(5, 4)) {
+            let subset: Vec<&str> = picked.iter().map(|&i| shares[i].expose()).collect();
+            assert_eq!(combine(&subset).unwrap().expose(), sk.expose(), "shares {:?}", picked);
+        }
+    }
+
+    #[test]
+    fn word_shares_recover_the_key() {
+        let sk = key();
+        let shares = split(&sk, 2, 3, ShareEncoding::Words).unwrap();
+        for picked in arrangements(3, 2) {
+            let subset: Vec<&str> = picked.iter().map(|&i| shares[i].expose()).collect();
+            assert_eq!(combine(&subset).unwrap().expose(), sk.expose(), "shares {:?}", picked);
+        }
+    }
+
+    #[test]
+    fn too_few_shares_are_rejected() {
+        let shares = split(&key(), 3, 5, ShareEncoding::Base58).unwrap();
+        assert!(combine(&[shares[0].expose(), shares[1].expose()]).is_err());
+    }
+
+    #[test]
+    fn mixed_sets_are_rejected() {
+        let sk = key();
+        let a = split(&sk, 2, 3, ShareEncoding::Base58).unwrap();
+        let b = loop {
+            let b = split(&sk, 2, 3, ShareEncoding::Base58).unwrap();
+            if set_id(&b[0]) != set_id(&a[0]) {
+                break b;
+            }
+        };
+        assert!(combine(&[a[0].expose(), b[1].expose()]).is_err());
+    }
+
+    #[test]
+    fn duplicate_shares_are_rejected() {
+        let shares = split(&key(), 2, 3, ShareEncoding::Base58).unwrap();
+        assert!(combine(&[shares[0].expose(), shares[0].expose()]).is_err());
+    }
+
+    #[test]
+    fn mistyped_shares_are_rejected() {
+        let shares = split(&key(), 2, 3, ShareEncoding::Base58).unwrap();
+        let mut bytes = bs58::decode(shares[0].expose()).into_vec().unwrap();
+        bytes[HEADER_LEN] ^= 1;
+        let mistyped = bs58::encode(&bytes).into_string();
+        assert!(combine(&[mistyped.as_str(), shares[1].expose()]).is_err());
+    }
+
+    #[test]
+    fn invalid_thresholds_are_rejected() {
+        assert!(split(&key(), 1, 3, ShareEncoding::Base58).is_err());
+        assert!(split(&key(), 4, 3, ShareEncoding::Base58).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::wallet::error::WalletError;
+use crate::wallet::keys;
+use crate::wallet::keystore::Keystore;
+use crate::wallet::secret::PrivateKey;
+use crate::wallet::session::Session;
+use crate::wallet::storage;
+
+/// Something that signs as one account. Transactions, multisig co-signatures and messages are all
+/// signed through this trait, so a backend that keeps its key somewhere else only has to provide
+/// these two methods.
+pub trait Signer {
+    fn public_key(&self) -> [u8; 48];
+
+    /// Signs `msg` under the domain separation tag `dst`; callers choose the tag, never the signer.
+    fn sign(&self, msg: &[u8], dst: &[u8]) -> Result<[u8; 96], WalletError>;
+}
+
+/// A key held in memory, e.g. a fixed key for tests.
+impl Signer for PrivateKey {
+    fn public_key(&self) -> [u8; 48] {
+        keys::public_key_bytes(self)
+    }
+
+    fn sign(&self, msg: &[u8], dst: &[u8]) -> Result<[u8; 96], WalletError> {
+        keys::sign_with_dst(self, msg, dst)
+    }
+}
+
+/// An account from the file keystore, decrypted from its key file or taken from the unlocked session.
+#[derive(Debug)]
+pub struct KeystoreSigner {
+    key: PrivateKey,
+    public_key: [u8; 48],
+}
+
+impl KeystoreSigner {
+    pub fn unlock(keystore: &Keystore, account_id: &str, password: &str) -> Result<Self, WalletError> {
+        let private_key_base58 = storage::load_and_decrypt(password, &keystore.key_path(account_id))?;
+        Ok(Self::new(keys::decode_private_key_base58(private_key_base58.expose())?))
+    }
+
+    /// Fails with `Locked` unless the session has `account_id` unlocked.
+    pub fn from_session(session: &Session, account_id: &str) -> Result<Self, WalletError> {
+        let key = session.with_private_key(account_id, |sk| keys::decode_private_key_base58(sk.expose()))?;
+        Ok(Self::new(key))
+    }
+
+    fn new(key: PrivateKey) -> Self {
+        KeystoreSigner {
+            public_key: key.public_key(),
+            key,
+        }
+    }
+}
+
+impl Signer for KeystoreSigner {
+    fn public_key(&self) -> [u8; 48] {
+        self.public_key
+    }
+
+    fn sign(&self, msg: &[u8], dst: &[u8]) -> Result<[u8; 96], WalletError> {
+        self.key.sign(msg, dst)
+    }
+}
@@ -1,60 +1,474 @@
 use crate::wallet::error::WalletError;
-use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit};
+use crate::wallet::keys;
+use crate::wallet::secret::SecretString;
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit,
+};
 use argon2::Argon2;
 use rand::RngCore;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
 
 const SALT_LEN: usize = 16;
 const NONCE_LEN: usize = 12;
 const TAG_LEN: usize = 16;
 
+/// Current `WalletFile::version`. Files without a version are the legacy bare blob (version 1).
+const FORMAT_VERSION: u32 = 2;
+const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+
+/// Self-describing encrypted key file. Everything needed to decrypt it, other than the password,
+/// is recorded here so KDF costs or the cipher can change without stranding older files.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct WalletFile {
+    version: u32,
+    #[serde(flatten)]
+    sealed: Sealed,
+    /// Base58 public key, readable without the password.
+    public_key: String,
+}
+
+/// Password-encrypted data with the parameters needed to decrypt it; the body of a key file and of
+/// a backup bundle.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Sealed {
+    kdf: KdfParams,
+    cipher: CipherParams,
+    /// Base64 ciphertext including the GCM tag.
+    ciphertext: String,
+}
+
+/// Argon2 cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KdfCost {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfCost {
+    /// Same as `argon2::Params::default()`, which every legacy file was written with.
+    fn default() -> Self {
+        KdfPreset::Interactive.cost()
+    }
+}
+
+/// Named KDF strengths offered to the user. The `argon2` crate computes lanes sequentially, so
+/// extra parallelism would only add time; presets scale memory and passes instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KdfPreset {
+    /// 19 MiB, 2 passes: the Argon2 default and OWASP's minimum recommendation.
+    Interactive,
+    /// 64 MiB, 3 passes.
+    Moderate,
+    /// 256 MiB, 4 passes.
+    Paranoid,
+}
+
+impl KdfPreset {
+    pub fn cost(self) -> KdfCost {
+        let (memory_kib, iterations) = match self {
+            KdfPreset::Interactive => (19 * 1024, 2),
+            KdfPreset::Moderate => (64 * 1024, 3),
+            KdfPreset::Paranoid => (256 * 1024, 4),
+        };
+        KdfCost {
+            memory_kib,
+            iterations,
+            parallelism: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct KdfParams {
+    /// `argon2id`, `argon2i` or `argon2d`.
+    algorithm: String,
+    /// Argon2 version number (`19` is 0x13).
+    version: u32,
+    #[serde(flatten)]
+    cost: KdfCost,
+    /// Base64.
+    salt: String,
+}
+
+/// The most a file may ask of the KDF: four times `KdfPreset::Paranoid` in memory and passes.
+/// Costs are read before the password can be checked, so without a ceiling a crafted file could
+/// make an unlock allocate gigabytes or run for hours.
+const MAX_KDF_MEMORY_KIB: u32 = 4 * 256 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 4 * 4;
+const MAX_KDF_PARALLELISM: u32 = 16;
+
+impl KdfCost {
+    fn check_ceiling(&self) -> Result<(), WalletError> {
+        if self.memory_kib > MAX_KDF_MEMORY_KIB
+            || self.iterations > MAX_KDF_ITERATIONS
+            || self.parallelism > MAX_KDF_PARALLELISM
+        {
+            return Err(WalletError::CorruptFile(format!(
+                "KDF cost ({} KiB, {} passes, {} lanes) exceeds what this app accepts",
+                self.memory_kib, self.iterations, self.parallelism
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl KdfParams {
+    fn argon2id(cost: KdfCost, salt: &[u8]) -> Self {
+        KdfParams {
+            algorithm: "argon2id".to_string(),
+            version: argon2::Version::V0x13 as u32,
+            cost,
+            salt: BASE64.encode(salt),
+        }
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>, WalletError> {
+        let algorithm = match self.algorithm.as_str() {
+            "argon2id" => argon2::Algorithm::Argon2id,
+            "argon2i" => argon2::Algorithm::Argon2i,
+            "argon2d" => argon2::Algorithm::Argon2d,
+            other => return Err(WalletError::CorruptFile(format!("Unsupported KDF: {}", other))),
+        };
+        let version = argon2::Version::try_from(self.version)
+            .map_err(|_| WalletError::CorruptFile(format!("Unsupported Argon2 version: {}", self.version)))?;
+        let params = argon2::Params::new(
+            self.cost.memory_kib,
+            self.cost.iterations,
+            self.cost.parallelism,
+            Some(32),
+        )
+        .map_err(|e| WalletError::CorruptFile(format!("Invalid Argon2 parameters: {}", e)))?;
+        Ok(Argon2::new(algorithm, version, params))
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CipherParams {
+    algorithm: String,
+    /// Base64.
+    nonce: String,
+}
+
 pub fn encrypt_and_store(
-    private_key_base58: &str,
+    private_key_base58: &SecretString,
     password: &str,
     path: &Path,
+    cost: KdfCost,
 ) -> Result<(), WalletError> {
+    let public_key = keys::public_key_from_private_base58(private_key_base58.expose())?;
+    let file = WalletFile {
+        version: FORMAT_VERSION,
+        sealed: seal(
+            private_key_base58.expose().as_bytes(),
+            password,
+            cost,
+            &aad(FORMAT_VERSION, &public_key),
+        )?,
+        public_key,
+    };
+    let data = serde_json::to_vec_pretty(&file).map_err(|e| WalletError::Storage(e.to_string()))?;
+    write_atomic(path, &data)
+}
+
+/// Decrypts either format. Legacy files are left as they are; the next `encrypt_and_store` of the
+/// same key rewrites them in the current format.
+pub fn load_and_decrypt(password: &str, path: &Path) -> Result<SecretString, WalletError> {
+    let (kdf, nonce, ciphertext, aad) = read_file(path)?;
+    let plaintext = decrypt(password, &kdf, &nonce, &ciphertext, &aad)?;
+    let text = std::str::from_utf8(&plaintext).map_err(|_| WalletError::DecryptionFailed)?;
+    Ok(SecretString::new(text.to_string()))
+}
+
+/// Encrypts `plaintext` under a fresh salt and nonce. `aad` is authenticated but not stored, so
+/// the same bytes must be passed to `open`.
+pub fn seal(plaintext: &[u8], password: &str, cost: KdfCost, aad: &[u8]) -> Result<Sealed, WalletError> {
     let mut salt = [0u8; SALT_LEN];
     rand::rng().fill_bytes(&mut salt);
-    let key = derive_key(password, &salt)?;
-    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| WalletError::Crypto(e.to_string()))?;
+    let kdf = KdfParams::argon2id(cost, &salt);
+    let key = derive_key(password, &kdf)?;
+    let cipher = Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::Crypto(e.to_string()))?;
     let mut nonce = [0u8; NONCE_LEN];
     rand::rng().fill_bytes(&mut nonce);
-    let plaintext = private_key_base58.as_bytes();
     let ciphertext = cipher
-        .encrypt((&nonce).into(), plaintext)
+        .encrypt((&nonce).into(), Payload { msg: plaintext, aad })
         .map_err(|_| WalletError::Crypto("Encryption failed".into()))?;
-    let mut out = salt.to_vec();
-    out.extend_from_slice(&nonce);
-    out.extend_from_slice(&ciphertext);
+    Ok(Sealed {
+        kdf,
+        cipher: CipherParams {
+            algorithm: CIPHER_AES_256_GCM.to_string(),
+            nonce: BASE64.encode(nonce),
+        },
+        ciphertext: BASE64.encode(&ciphertext),
+    })
+}
+
+pub fn open(sealed: &Sealed, password: &str, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+    let (nonce, ciphertext) = sealed.parts()?;
+    decrypt(password, &sealed.kdf, &nonce, &ciphertext, aad)
+}
+
+impl Sealed {
+    fn parts(&self) -> Result<(Vec<u8>, Vec<u8>), WalletError> {
+        if self.cipher.algorithm != CIPHER_AES_256_GCM {
+            return Err(WalletError::CorruptFile(format!(
+                "Unsupported cipher: {}",
+                self.cipher.algorithm
+            )));
+        }
+        self.kdf.cost.check_ceiling()?;
+        let nonce = decode_field(&self.cipher.nonce, "nonce")?;
+        let ciphertext = decode_field(&self.ciphertext, "ciphertext")?;
+        if nonce.len() != NONCE_LEN || ciphertext.len() < TAG_LEN {
+            return Err(WalletError::CorruptFile("Truncated nonce or ciphertext".into()));
+        }
+        Ok((nonce, ciphertext))
+    }
+}
+
+fn decrypt(
+    password: &str,
+    kdf: &KdfParams,
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+    let key = derive_key(password, kdf)?;
+    let cipher = Aes256Gcm::new_from_slice(&*key).map_err(|e| WalletError::Crypto(e.to_string()))?;
+    let plaintext = cipher
+        .decrypt(aes_gcm::Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| WalletError::DecryptionFailed)?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Writes through a temporary file so a crash never leaves `path` half-written.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), WalletError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| WalletError::Storage(e.to_string()))?;
     }
-    std::fs::write(path, BASE64.encode(&out)).map_err(|e| WalletError::Storage(e.to_string()))?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    std::fs::write(&tmp_path, data).map_err(|e| WalletError::Storage(e.to_string()))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| WalletError::Storage(e.to_string()))?;
     Ok(())
 }
 
-pub fn load_and_decrypt(password: &str, path: &Path) -> Result<String, WalletError> {
+/// Checks that `data` is a key file in either format, returning the public key it records (legacy
+/// files record none).
+pub fn inspect_key_file(data: &[u8]) -> Result<Option<String>, WalletError> {
+    if data.trim_ascii_start().starts_with(b"{") {
+        let file = parse_wallet_file(data)?;
+        file.sealed.parts()?;
+        Ok(Some(file.public_key))
+    } else {
+        parse_legacy_blob(data)?;
+        Ok(None)
+    }
+}
+
+/// The KDF cost a key file was written with, read without the password.
+pub fn kdf_cost(path: &Path) -> Result<KdfCost, WalletError> {
+    Ok(read_file(path)?.0.cost)
+}
+
+/// Times one key derivation at `cost`, i.e. roughly how long an unlock will take on this machine.
+pub fn benchmark_kdf(cost: KdfCost) -> Result<std::time::Duration, WalletError> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::rng().fill_bytes(&mut salt);
+    let kdf = KdfParams::argon2id(cost, &salt);
+    let started = std::time::Instant::now();
+    derive_key("benchmark", &kdf)?;
+    Ok(started.elapsed())
+}
+
+/// The KDF parameters, nonce, ciphertext and associated data of a key file in either format.
+type KeyFileParts = (KdfParams, Vec<u8>, Vec<u8>, Vec<u8>);
+
+fn read_file(path: &Path) -> Result<KeyFileParts, WalletError> {
     let data = std::fs::read(path).map_err(|e| WalletError::Storage(e.to_string()))?;
-    let decoded = BASE64.decode(&data).map_err(|_| WalletError::DecryptionFailed)?;
+    if data.trim_ascii_start().starts_with(b"{") {
+        let file = parse_wallet_file(&data)?;
+        let (nonce, ciphertext) = file.sealed.parts()?;
+        Ok((file.sealed.kdf, nonce, ciphertext, aad(file.version, &file.public_key)))
+    } else {
+        let (kdf, nonce, ciphertext) = parse_legacy_blob(&data)?;
+        Ok((kdf, nonce, ciphertext, Vec::new()))
+    }
+}
+
+/// Binds the readable header to the ciphertext, so editing the recorded public key or version
+/// makes the file fail to decrypt.
+fn aad(version: u32, public_key: &str) -> Vec<u8> {
+    format!("wallet/{}/{}", version, public_key).into_bytes()
+}
+
+fn parse_wallet_file(data: &[u8]) -> Result<WalletFile, WalletError> {
+    let file: WalletFile = serde_json::from_slice(data)
+        .map_err(|e| WalletError::CorruptFile(e.to_string()))?;
+    if file.version > FORMAT_VERSION {
+        return Err(WalletError::CorruptFile(format!(
+            "Wallet file version {} is newer than this app supports",
+            file.version
+        )));
+    }
+    Ok(file)
+}
+
+/// Version 1: base64 of `salt || nonce || ciphertext`, always with the default Argon2 parameters.
+fn parse_legacy_blob(data: &[u8]) -> Result<(KdfParams, Vec<u8>, Vec<u8>), WalletError> {
+    let decoded = BASE64
+        .decode(data.trim_ascii())
+        .map_err(|_| WalletError::CorruptFile("Not a wallet file".into()))?;
     if decoded.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
-        return Err(WalletError::DecryptionFailed);
+        return Err(WalletError::CorruptFile("File is truncated".into()));
     }
     let (salt, rest) = decoded.split_at(SALT_LEN);
-    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
-    let key = derive_key(password, salt)?;
-    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| WalletError::Crypto(e.to_string()))?;
-    let nonce = aes_gcm::Nonce::from_slice(nonce_bytes);
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| WalletError::DecryptionFailed)?;
-    String::from_utf8(plaintext).map_err(|_| WalletError::DecryptionFailed)
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok((KdfParams::argon2id(KdfCost::default(), salt), nonce.to_vec(), ciphertext.to_vec()))
+}
+
+fn decode_field(value: &str, name: &str) -> Result<Vec<u8>, WalletError> {
+    BASE64
+        .decode(value)
+        .map_err(|_| WalletError::CorruptFile(format!("Invalid {} encoding", name)))
+}
+
+/// Moves `path` into `backup_dir` under a name stamped with the current Unix time in milliseconds,
+/// returning where it went.
+pub fn backup_file(path: &Path, backup_dir: &Path) -> Result<PathBuf, WalletError> {
+    std::fs::create_dir_all(backup_dir).map_err(|e| WalletError::Storage(e.to_string()))?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "wallet".to_string());
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let backup_path = backup_dir.join(format!("{}-{}.enc", stem, millis));
+    if backup_path.exists() {
+        return Err(WalletError::Storage(format!(
+            "Backup {} already exists",
+            backup_path.display()
+        )));
+    }
+    std::fs::rename(path, &backup_path).map_err(|e| WalletError::Storage(e.to_string()))?;
+    Ok(backup_path)
 }
 
-fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], WalletError> {
-    let mut out = [0u8; 32];
-    Argon2::default()
-        .hash_password_into(password.as_bytes(), salt, &mut out)
+fn derive_key(password: &str, kdf: &KdfParams) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+    let salt = decode_field(&kdf.salt, "salt")?;
+    let mut out = Zeroizing::new([0u8; 32]);
+    kdf.argon2()?
+        .hash_password_into(password.as_bytes(), &salt, &mut *out)
         .map_err(|e| WalletError::Crypto(e.to_string()))?;
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PASSWORD: &str = "correct horse";
+    /// Far below any preset; these tests are about the file format, not the KDF.
+    const CHEAP: KdfCost = KdfCost {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("amadeus-storage-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A key file as it was written before `WalletFile`: base64 of `salt || nonce || ciphertext`.
+    fn legacy_blob(private_key_base58: &str) -> String {
+        let (salt, nonce) = ([1u8; SALT_LEN], [2u8; NONCE_LEN]);
+        let key = derive_key(PASSWORD, &KdfParams::argon2id(KdfCost::default(), &salt)).unwrap();
+        let ciphertext = Aes256Gcm::new_from_slice(&*key)
+            .unwrap()
+            .encrypt((&nonce).into(), private_key_base58.as_bytes())
+            .unwrap();
+        BASE64.encode([&salt[..], &nonce[..], &ciphertext[..]].concat())
+    }
+
+    /// Rewrites the field at JSON `pointer` in the key file at `path`.
+    fn edit_file(path: &Path, pointer: &str, value: serde_json::Value) {
+        let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        *json.pointer_mut(pointer).unwrap() = value;
+        std::fs::write(path, serde_json::to_vec(&json).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn legacy_blob_opens_and_is_rewritten_as_v2() {
+        let sk = keys::generate_private_key_base58().unwrap();
+        let path = temp_dir().join("wallet.enc");
+        std::fs::write(&path, legacy_blob(sk.expose())).unwrap();
+
+        assert_eq!(inspect_key_file(&std::fs::read(&path).unwrap()).unwrap(), None);
+        assert_eq!(kdf_cost(&path).unwrap(), KdfCost::default());
+        assert_eq!(load_and_decrypt(PASSWORD, &path).unwrap().expose(), sk.expose());
+
+        encrypt_and_store(&sk, PASSWORD, &path, kdf_cost(&path).unwrap()).unwrap();
+        let file = parse_wallet_file(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(file.version, FORMAT_VERSION);
+        assert_eq!(file.public_key, keys::public_key_from_private_base58(sk.expose()).unwrap());
+        assert_eq!(file.sealed.kdf.cost, KdfCost::default());
+        assert_eq!(load_and_decrypt(PASSWORD, &path).unwrap().expose(), sk.expose());
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let path = temp_dir().join("key.enc");
+        encrypt_and_store(&keys::generate_private_key_base58().unwrap(), PASSWORD, &path, CHEAP).unwrap();
+        assert!(matches!(load_and_decrypt("battery staple", &path), Err(WalletError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn rejects_a_tampered_header() {
+        let path = temp_dir().join("key.enc");
+        let other = keys::generate_private_key_base58().unwrap();
+        let other = keys::public_key_from_private_base58(other.expose()).unwrap();
+        for (pointer, value) in [("/public_key", json!(other)), ("/version", json!(1))] {
+            encrypt_and_store(&keys::generate_private_key_base58().unwrap(), PASSWORD, &path, CHEAP).unwrap();
+            assert!(load_and_decrypt(PASSWORD, &path).is_ok());
+            edit_file(&path, pointer, value);
+            assert!(matches!(load_and_decrypt(PASSWORD, &path), Err(WalletError::DecryptionFailed)));
+        }
+    }
+
+    #[test]
+    fn rejects_a_tampered_ciphertext() {
+        let path = temp_dir().join("key.enc");
+        encrypt_and_store(&keys::generate_private_key_base58().unwrap(), PASSWORD, &path, CHEAP).unwrap();
+        let file = parse_wallet_file(&std::fs::read(&path).unwrap()).unwrap();
+        let mut ciphertext = BASE64.decode(&file.sealed.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        edit_file(&path, "/ciphertext", json!(BASE64.encode(ciphertext)));
+        assert!(matches!(load_and_decrypt(PASSWORD, &path), Err(WalletError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn refuses_kdf_costs_above_the_ceiling() {
+        let path = temp_dir().join("key.enc");
+        encrypt_and_store(&keys::generate_private_key_base58().unwrap(), PASSWORD, &path, CHEAP).unwrap();
+        edit_file(&path, "/kdf/memory_kib", json!(MAX_KDF_MEMORY_KIB + 1));
+        assert!(matches!(load_and_decrypt(PASSWORD, &path), Err(WalletError::CorruptFile(_))));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let path = temp_dir().join("key.enc");
+        encrypt_and_store(&keys::generate_private_key_base58().unwrap(), PASSWORD, &path, CHEAP).unwrap();
+        edit_file(&path, "/version", json!(FORMAT_VERSION + 1));
+        assert!(matches!(load_and_decrypt(PASSWORD, &path), Err(WalletError::CorruptFile(_))));
+    }
+}
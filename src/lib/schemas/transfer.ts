@@ -7,8 +7,11 @@ const base58AddressSchema = z
 
 const amountSchema = z
     .string()
+    .trim()
     .min(1, "Amount is required")
-    .refine((v) => !Number.isNaN(Number(v)) && Number(v) > 0, "Amount must be a positive number");
+    .regex(/^(\d+(\.\d+)?|\.\d+)$/, "Amount must be a plain decimal number")
+    .refine((v) => (v.split(".")[1] ?? "").replace(/0+$/, "").length <= 9, "At most 9 decimal places")
+    .refine((v) => /[1-9]/.test(v), "Amount must be a positive number");
 
 export const sendTransferSchema = z.object({
     recipientAddress: base58AddressSchema,
@@ -6,7 +6,24 @@ export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
 }
 
+/** What every backend command rejects with; branch on `code`, show or localize `message`. */
+export type CommandError = {
+    code: string;
+    message: string;
+    details?: Record<string, unknown>;
+};
+
+export function isCommandError(error: unknown): error is CommandError {
+    return (
+        typeof error === "object" &&
+        error !== null &&
+        typeof (error as CommandError).code === "string" &&
+        typeof (error as CommandError).message === "string"
+    );
+}
+
 export function getErrorMessage(error: unknown): string {
+    if (isCommandError(error)) return error.message;
     return error instanceof Error ? error.message : String(error);
 }
 
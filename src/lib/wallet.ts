@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { globals } from "@/lib/globals";
+import { addRecentAddress, getRecentAddresses } from "@/lib/recentAddresses";
 
 const STORAGE_API_URL_KEY = "amadeus-api-url";
 
@@ -16,10 +17,75 @@ export function setAmadeusApiUrl(url: string): void {
   else localStorage.removeItem(STORAGE_API_URL_KEY);
 }
 
+/** How the backend's shared HTTP client is built; saved by the backend. */
+export type NetworkSettings = {
+  request_timeout_secs: number;
+  connect_timeout_secs: number;
+  health_timeout_secs: number;
+  pool_idle_timeout_secs: number;
+  pool_max_idle_per_host: number;
+  user_agent: string;
+  /** http://, https://, socks5:// or socks5h:// URL; null uses the system proxy settings. */
+  proxy: string | null;
+  /** Extra PEM certificates to trust, e.g. an internal CA. */
+  ca_certificates: string[];
+  /** Nodes to fail over to; the configured API URL is always tried too, with priority 0 unless listed. */
+  nodes: NodeEndpoint[];
+};
+
+/** Lower priority is preferred. */
+export type NodeEndpoint = {
+  url: string;
+  priority: number;
+};
+
+export async function getNetworkSettings(): Promise<NetworkSettings> {
+  return invoke<NetworkSettings>("get_network_settings");
+}
+
+/**
+ * Set when the saved settings could not be loaded or built (e.g. a certificate went bad). Until they
+ * are fixed with setNetworkSettings, every request fails with code "settings" rather than going out
+ * without the configured proxy.
+ */
+export async function getNetworkSettingsError(): Promise<NetworkError | null> {
+  return invoke<NetworkError | null>("get_network_settings_error");
+}
+
+/** Throws (and keeps the current settings) if a proxy URL or certificate is invalid. */
+export async function setNetworkSettings(settings: NetworkSettings): Promise<void> {
+  return invoke("set_network_settings", { settings });
+}
+
+/** Stable codes for NetworkError; branch on these rather than on the message. */
+export type NetworkErrorCode =
+  | "timeout"
+  | "dns"
+  | "connect"
+  | "tls"
+  | "interrupted"
+  | "redirect"
+  | "http_status"
+  | "cloudflare"
+  | "decode"
+  | "not_found"
+  | "invalid_url"
+  | "invalid_transaction"
+  | "settings";
+
+/** A network-layer error as returned (or thrown) by the backend; same shape as CommandError. */
+export type NetworkError = {
+  code: NetworkErrorCode;
+  message: string;
+  /** status for http_status and cloudflare; body (the node's error text) for http_status. */
+  details?: { status: number; body?: string };
+};
+
 export type HealthCheckResult = {
   ok: boolean;
   status_code?: number;
   message: string;
+  error?: NetworkError | null;
 };
 
 export async function checkNodeHealth(apiUrl: string): Promise<HealthCheckResult> {
@@ -28,20 +94,51 @@ export async function checkNodeHealth(apiUrl: string): Promise<HealthCheckResult
   });
 }
 
+export type NodeStatus = {
+  url: string;
+  priority: number;
+  /** null until the node has been checked or used. */
+  healthy: boolean | null;
+  latency_ms: number | null;
+  consecutive_failures: number;
+  last_error: string | null;
+  last_checked_ms: number | null;
+};
+
+export type NodePoolStatus = {
+  /** The node that last answered, or the one that will be tried first. */
+  current: string | null;
+  /** Best first, in the order requests try them. */
+  nodes: NodeStatus[];
+};
+
+export async function getNodePoolStatus(): Promise<NodePoolStatus> {
+  return invoke<NodePoolStatus>("get_node_pool_status", { apiUrl: getAmadeusApiUrl() });
+}
+
+/** Health-checks every node and returns the updated pool. */
+export async function checkNodePool(): Promise<NodePoolStatus> {
+  return invoke<NodePoolStatus>("check_node_pool", { apiUrl: getAmadeusApiUrl() });
+}
+
 const BALANCE_DECIMALS = 9;
 
 export type BalanceResult = {
   ok: boolean;
   balance_flat?: string;
+  /** balance_flat formatted as a decimal string, e.g. "5.25" */
+  balance?: string;
   error?: string;
+  error_code?: NetworkErrorCode;
   network_error?: boolean;
 };
 
 export async function getBalance(addressBase58: string): Promise<BalanceResult> {
-  return invoke<BalanceResult>("get_balance", {
+  const r = await invoke<Omit<BalanceResult, "error"> & { error?: NetworkError | null }>("get_balance", {
     apiUrl: getAmadeusApiUrl(),
     addressBase58: addressBase58.trim(),
   });
+  return { ...r, error: r.error?.message, error_code: r.error?.code };
 }
 
 /** Formats flat balance (9 decimals) for display: e.g. "5000000000" -> "5.000000000" */
@@ -60,24 +157,211 @@ export function formatBalance(balanceFlat: string | undefined): string {
   }
 }
 
-export async function createWallet(password: string): Promise<string> {
-  return invoke<string>("wallet_create", { password });
+/** Adds a new generated account, selects it, and returns its private key for backup. */
+export async function createWallet(password: string, label?: string): Promise<string> {
+  return invoke<string>("wallet_create", { password, label: label?.trim() || null });
 }
 
+/**
+ * Adds an existing private key as a new account and selects it. Fails if the key is already in
+ * the wallet unless `force` is set; then the old key file is backed up and its path returned.
+ */
 export async function importWallet(
   privateKeyBase58: string,
-  password: string
-): Promise<void> {
-  return invoke("wallet_import", {
+  password: string,
+  label?: string,
+  force = false
+): Promise<string | null> {
+  return invoke<string | null>("wallet_import", {
     privateKeyBase58,
     password,
+    label: label?.trim() || null,
+    force,
   });
 }
 
+/**
+ * The selected account's key as a 48-word recovery phrase (BIP39 English words). The password is
+ * always required; a passphrase, if given, is needed again to restore.
+ */
+export async function exportMnemonic(password: string, passphrase?: string): Promise<string> {
+  return invoke<string>("wallet_export_mnemonic", { password, passphrase: passphrase || null });
+}
+
+/** Adds the account behind a recovery phrase; otherwise behaves like importWallet. */
+export async function importMnemonic(
+  mnemonic: string,
+  password: string,
+  passphrase?: string,
+  label?: string,
+  force = false
+): Promise<string | null> {
+  return invoke<string | null>("wallet_import_mnemonic", {
+    mnemonic: mnemonic.trim(),
+    passphrase: passphrase || null,
+    password,
+    label: label?.trim() || null,
+    force,
+  });
+}
+
+export type ShareEncoding = "base58" | "words";
+
+/**
+ * Splits the selected account's key into `shares` Shamir shares, any `threshold` of which restore
+ * it with recoverKeyFromShares. The password is always required.
+ */
+export async function splitKey(
+  password: string,
+  threshold: number,
+  shares: number,
+  encoding: ShareEncoding = "base58"
+): Promise<string[]> {
+  return invoke<string[]>("wallet_split_key", { password, threshold, shares, encoding });
+}
+
+/** Combines shares (Base58 or words, any order) and imports the key like importWallet. */
+export async function recoverKeyFromShares(
+  shares: string[],
+  password: string,
+  label?: string,
+  force = false
+): Promise<string | null> {
+  return invoke<string | null>("wallet_recover_key", {
+    shares: shares.map((s) => s.trim()).filter(Boolean),
+    password,
+    label: label?.trim() || null,
+    force,
+  });
+}
+
+export type BackupSettings = {
+  address_book: { address: string; label?: string | null }[];
+  network: { api_url?: string | null };
+};
+
+export type BackupMetadata = {
+  app_version: string;
+  /** Unix seconds */
+  created_at: number;
+  account_count: number;
+};
+
+export type RestoredBackup = {
+  metadata: BackupMetadata;
+  added: Account[];
+  /** Labels of accounts that were already in this wallet. */
+  skipped: string[];
+  settings: BackupSettings;
+};
+
+/**
+ * Writes every account plus the address book and node URL to an encrypted backup file at `path`
+ * (which must not exist yet). Accounts inside keep their own passwords.
+ */
+export async function exportBackup(backupPassword: string, path: string): Promise<BackupMetadata> {
+  const stored = localStorage.getItem(STORAGE_API_URL_KEY);
+  const settings: BackupSettings = {
+    address_book: getRecentAddresses().map((address) => ({ address })),
+    network: { api_url: stored?.trim() || null },
+  };
+  return invoke<BackupMetadata>("wallet_export_backup", { backupPassword, path: path.trim(), settings });
+}
+
+/** Restores accounts missing from this wallet and re-applies the backed-up address book and node URL. */
+export async function importBackup(backupPassword: string, path: string): Promise<RestoredBackup> {
+  const restored = await invoke<RestoredBackup>("wallet_import_backup", { backupPassword, path: path.trim() });
+  const { address_book, network } = restored.settings;
+  [...address_book].reverse().forEach((entry) => addRecentAddress(entry.address));
+  if (network.api_url && !localStorage.getItem(STORAGE_API_URL_KEY)) setAmadeusApiUrl(network.api_url);
+  return restored;
+}
+
+export type Account = {
+  id: string;
+  label: string;
+  /** Unix seconds */
+  created_at: number;
+  /** Null only for a pre-keystore wallet that has not been unlocked yet. */
+  public_key: string | null;
+  /** Present for accounts derived from another account's key. */
+  derivation?: { root: string; index: number };
+};
+
+export type AccountList = {
+  selected: string | null;
+  accounts: Account[];
+};
+
+/**
+ * Adds child account `index` of the selected account (default: the next one) as a new account
+ * under the same password. One backup of the selected account restores all of its children.
+ */
+export async function deriveAccount(password: string, index?: number, label?: string): Promise<Account> {
+  return invoke<Account>("wallet_derive_account", {
+    password,
+    index: index ?? null,
+    label: label?.trim() || null,
+  });
+}
+
+/**
+ * After restoring a root account, re-adds its children that have on-chain activity, stopping
+ * after `gapLimit` (default 5) unused ones in a row. Returns the accounts added.
+ */
+export async function scanAccounts(password: string, apiUrl: string, gapLimit?: number): Promise<Account[]> {
+  return invoke<Account[]>("wallet_scan_accounts", { password, apiUrl, gapLimit: gapLimit ?? null });
+}
+
+export async function listAccounts(): Promise<AccountList> {
+  return invoke<AccountList>("wallet_list_accounts", {});
+}
+
+export async function renameAccount(id: string, label: string): Promise<void> {
+  return invoke("wallet_rename_account", { id, label: label.trim() });
+}
+
+/** Makes `id` the account that unlock, signing and password changes act on. */
+export async function selectAccount(id: string): Promise<void> {
+  return invoke("wallet_select_account", { id });
+}
+
+/**
+ * Removes an account; requires that account's password. Its key file is moved to the wallet's backups
+ * folder, and that path is returned.
+ */
+export async function deleteAccount(id: string, password: string): Promise<string | null> {
+  return invoke<string | null>("wallet_delete_account", { id, password });
+}
+
 export async function unlockWallet(password: string): Promise<string> {
   return invoke<string>("wallet_unlock", { password });
 }
 
+export async function lockWallet(): Promise<void> {
+  return invoke<void>("wallet_lock", {});
+}
+
+export type SessionStatus = {
+  unlocked: boolean;
+  account_id: string | null;
+  public_key: string | null;
+  idle_timeout_secs: number;
+  /** Seconds until auto-lock if the key is not used again. */
+  locks_in_secs: number | null;
+};
+
+export async function getSessionStatus(): Promise<SessionStatus> {
+  return invoke<SessionStatus>("wallet_session_status", {});
+}
+
+export async function setAutoLockTimeout(timeoutSecs: number): Promise<void> {
+  return invoke<void>("wallet_set_auto_lock", { timeoutSecs });
+}
+
+/** Event emitted when the session locks itself after sitting idle. */
+export const AUTO_LOCKED_EVENT = "wallet://auto-locked";
+
 export async function hasWallet(): Promise<boolean> {
   return invoke<boolean>("wallet_has", {});
 }
@@ -104,54 +388,425 @@ export async function changeWalletPassword(
   });
 }
 
-export async function validateAddress(addressBase58: string): Promise<boolean> {
-  return invoke<boolean>("wallet_validate_address", {
+export type KdfPreset = "interactive" | "moderate" | "paranoid";
+
+export type KdfCost = {
+  memory_kib: number;
+  iterations: number;
+  parallelism: number;
+};
+
+export type KdfBenchmark = KdfCost & {
+  preset: KdfPreset;
+  /** Approximate unlock time on this machine */
+  millis: number;
+};
+
+/** Argon2 cost of the selected account's key file. */
+export async function getKdfCost(): Promise<KdfCost> {
+  return invoke<KdfCost>("wallet_kdf_cost", {});
+}
+
+/** Re-encrypts the selected account's key with the given KDF preset. */
+export async function setKdfPreset(password: string, preset: KdfPreset): Promise<void> {
+  return invoke("wallet_set_kdf_preset", { password, preset });
+}
+
+/** Measures how long unlocking would take with the given KDF preset. */
+export async function benchmarkKdf(preset: KdfPreset): Promise<KdfBenchmark> {
+  return invoke<KdfBenchmark>("wallet_benchmark_kdf", { preset });
+}
+
+export type AddressValidation = {
+  valid: boolean;
+  error:
+    | { reason: "bad_base58" }
+    | { reason: "wrong_length"; len: number }
+    | { reason: "invalid_point" }
+    | null;
+  message: string | null;
+};
+
+/** Checks that the address is a real BLS12-381 public key, not just 48 bytes of Base58. */
+export async function validateAddress(addressBase58: string): Promise<AddressValidation> {
+  return invoke<AddressValidation>("wallet_validate_address", {
     addressBase58: addressBase58.trim(),
   });
 }
 
-/** Signs a transfer and returns JSON payload for the node API. */
+/**
+ * Signs a transfer and returns the payload for the node API. Pass `null` as the password to sign with
+ * the unlocked session, and a decimal `nonce` only to replace a pending transaction; otherwise the next
+ * one is reserved.
+ */
 export async function signTransaction(
-  password: string,
+  password: string | null,
   recipientBase58: string,
   amount: string,
-  memo: string
+  memo: string,
+  nonce?: string | null
 ): Promise<string> {
   return invoke<string>("wallet_sign_transaction", {
-    password,
+    password: password || null,
     recipientBase58: recipientBase58.trim(),
     amount: amount.trim(),
     memo: (memo ?? "").trim(),
+    nonce: nonce?.trim() || null,
   });
 }
 
+/** Nonces are decimal strings (nanosecond timestamps don't fit in a JS number). */
+export type NonceInfo = {
+  address: string;
+  last_used: string | null;
+  chain: string | null;
+  /** Used by the next transaction unless a nonce is given. */
+  next: string;
+};
+
+export async function getNonceStatus(addressBase58: string): Promise<NonceInfo> {
+  return invoke<NonceInfo>("wallet_nonce_status", { addressBase58: addressBase58.trim() });
+}
+
+/** Fetches the node's nonce for the address so new transactions are numbered above it. */
+export async function syncNonce(addressBase58: string): Promise<NonceInfo> {
+  return invoke<NonceInfo>("wallet_sync_nonce", {
+    apiUrl: getAmadeusApiUrl(),
+    addressBase58: addressBase58.trim(),
+  });
+}
+
+/** A contract call argument; each is sent to the chain as a binary. */
+export type ContractArg =
+  | { type: "string"; value: string }
+  | { type: "base58"; value: string }
+  /** Integer, sent as its decimal string. */
+  | { type: "int"; value: string }
+  /** Coin amount in whole units (e.g. "1.5"), sent as flat units. */
+  | { type: "amount"; value: string };
+
+export type Attachment = {
+  symbol: string;
+  /** Whole units, e.g. "1.5" */
+  amount: string;
+};
+
+/** Signs an arbitrary contract call and returns the Base58 signed transaction. */
+export async function signContractCall(
+  password: string | null,
+  contract: string,
+  fn: string,
+  args: ContractArg[],
+  attachment?: Attachment | null,
+  nonce?: string | null
+): Promise<string> {
+  return invoke<string>("wallet_sign_contract_call", {
+    password: password || null,
+    contract: contract.trim(),
+    function: fn.trim(),
+    args,
+    attachment: attachment ?? null,
+    nonce: nonce?.trim() || null,
+  });
+}
+
+export type TxAction = {
+  op: string;
+  /** Contract name (e.g. "Coin"), or Base58 address for deployed contracts. */
+  contract: string;
+  function: string;
+  /** Raw call arguments, Base58-encoded. */
+  args: string[];
+  attached_symbol?: string | null;
+  attached_amount?: string | null;
+};
+
+export type Transaction = {
+  signer: string;
+  nonce: string;
+  action: TxAction;
+};
+
+export type SignedTx = {
+  hash: string;
+  signature: string;
+  tx: Transaction;
+};
+
+/** Builds (without signing) the transfer that signTransaction would sign, for review. */
+export async function previewTransaction(
+  publicKeyBase58: string,
+  recipientBase58: string,
+  amount: string,
+  memo: string
+): Promise<Transaction> {
+  return invoke<Transaction>("wallet_preview_transaction", {
+    publicKeyBase58: publicKeyBase58.trim(),
+    recipientBase58: recipientBase58.trim(),
+    amount: amount.trim(),
+    memo: (memo ?? "").trim(),
+  });
+}
+
+/** Decodes a Base58 signed transaction; rejects if its hash or signature does not check out. */
+export async function verifyTransaction(signedTxBase58: string): Promise<SignedTx> {
+  return invoke<SignedTx>("wallet_verify_transaction", {
+    signedTxBase58: signedTxBase58.trim(),
+  });
+}
+
+/** Writes an unsigned transfer to a file for signing on an offline machine. */
+export async function exportUnsignedTransfer(
+  publicKeyBase58: string,
+  recipientBase58: string,
+  amount: string,
+  memo: string,
+  path: string,
+  nonce?: string | null
+): Promise<Transaction> {
+  return invoke<Transaction>("wallet_export_unsigned_transfer", {
+    publicKeyBase58: publicKeyBase58.trim(),
+    recipientBase58: recipientBase58.trim(),
+    amount: amount.trim(),
+    memo: memo.trim(),
+    nonce: nonce?.trim() || null,
+    path,
+  });
+}
+
+export async function exportUnsignedContractCall(
+  publicKeyBase58: string,
+  contract: string,
+  fn: string,
+  args: ContractArg[],
+  attachment: Attachment | null,
+  path: string,
+  nonce?: string | null
+): Promise<Transaction> {
+  return invoke<Transaction>("wallet_export_unsigned_contract_call", {
+    publicKeyBase58: publicKeyBase58.trim(),
+    contract: contract.trim(),
+    function: fn.trim(),
+    args,
+    attachment,
+    nonce: nonce?.trim() || null,
+    path,
+  });
+}
+
+/** Reads an unsigned transaction file for review before signing. */
+export async function inspectUnsignedTransaction(path: string): Promise<Transaction> {
+  return invoke<Transaction>("wallet_inspect_unsigned_transaction", { path });
+}
+
+/** Signs an unsigned transaction file with the selected account; pass null to use the unlocked session. */
+export async function signTransactionFile(
+  password: string | null,
+  inputPath: string,
+  outputPath: string
+): Promise<SignedTx> {
+  return invoke<SignedTx>("wallet_sign_transaction_file", {
+    password: password || null,
+    inputPath,
+    outputPath,
+  });
+}
+
+/** A co-signer's public key and proof of possession (a signature over that key), both Base58. */
+export type Cosigner = {
+  public_key: string;
+  proof: string;
+};
+
+/**
+ * A transaction collecting co-signatures; pass it between co-signers as JSON.
+ * Multisig is n-of-n: every co-signer must sign, there is no smaller threshold.
+ */
+export type PartialTx = {
+  format: string;
+  version: number;
+  /** Packed unsigned tx, Base58 */
+  tx: string;
+  cosigners: Cosigner[];
+  /** Co-signer public key -> signature, both Base58 */
+  signatures: Record<string, string>;
+};
+
+export type MultisigStatus = {
+  tx: Transaction;
+  hash: string;
+  signed: string[];
+  missing: string[];
+  complete: boolean;
+};
+
+/**
+ * The selected account's key and proof of possession; each co-signer shares theirs so the others
+ * can set up the shared account. Pass null to use the unlocked session.
+ */
+export async function multisigProof(password: string | null): Promise<Cosigner> {
+  return invoke<Cosigner>("wallet_multisig_proof", { password: password || null });
+}
+
+/** Shared account address for a set of co-signers; every co-signer must sign (n-of-n). */
+export async function multisigAddress(cosigners: Cosigner[]): Promise<string> {
+  return invoke<string>("wallet_multisig_address", { cosigners });
+}
+
+export async function createMultisigTransfer(
+  cosigners: Cosigner[],
+  recipientBase58: string,
+  amount: string,
+  memo: string,
+  nonce?: string | null
+): Promise<PartialTx> {
+  return invoke<PartialTx>("wallet_multisig_create_transfer", {
+    cosigners,
+    recipientBase58: recipientBase58.trim(),
+    amount: amount.trim(),
+    memo: memo.trim(),
+    nonce: nonce?.trim() || null,
+  });
+}
+
+/** Adds the selected account's signature; pass null to use the unlocked session. */
+export async function signMultisig(password: string | null, partial: PartialTx): Promise<PartialTx> {
+  return invoke<PartialTx>("wallet_multisig_sign", { password: password || null, partial });
+}
+
+/** Combines copies that co-signers signed in parallel. */
+export async function mergeMultisig(partials: PartialTx[]): Promise<PartialTx> {
+  return invoke<PartialTx>("wallet_multisig_merge", { partials });
+}
+
+export async function getMultisigStatus(partial: PartialTx): Promise<MultisigStatus> {
+  return invoke<MultisigStatus>("wallet_multisig_status", { partial });
+}
+
+/** Aggregates the signatures once every co-signer has signed (n-of-n); returns the Base58 signed transaction. */
+export async function finalizeMultisig(partial: PartialTx): Promise<string> {
+  return invoke<string>("wallet_multisig_finalize", { partial });
+}
+
+export type MessageInput =
+  /** UTF-8 text, signed as-is. */
+  | { type: "text"; value: string }
+  | { type: "base58"; value: string }
+  | { type: "base64"; value: string };
+
+export type SignedMessage = {
+  public_key: string;
+  /** Base58, 96 bytes */
+  signature: string;
+  /** Domain separation tag the signature was made under. */
+  dst: string;
+};
+
+/** Signs a message with the selected account; pass null to use the unlocked session. */
+export async function signMessage(
+  password: string | null,
+  message: MessageInput
+): Promise<SignedMessage> {
+  return invoke<SignedMessage>("wallet_sign_message", {
+    password: password || null,
+    message,
+  });
+}
+
+/** False when the signature does not match; throws on malformed input. */
+export async function verifyMessage(
+  publicKey: string,
+  message: MessageInput,
+  signature: string
+): Promise<boolean> {
+  return invoke<boolean>("wallet_verify_message", {
+    publicKey: publicKey.trim(),
+    message,
+    signature: signature.trim(),
+  });
+}
+
+/** Decoded vecpak value; ints are decimal strings and binaries are Base58. */
+export type VecpakTerm =
+  | { type: "nil" }
+  | { type: "bool"; value: boolean }
+  | { type: "int"; value: string }
+  | { type: "binary"; value: string }
+  | { type: "list"; value: VecpakTerm[] }
+  | { type: "prop_list"; value: [VecpakTerm, VecpakTerm][] };
+
+/** Decodes a Base58 vecpak blob (e.g. a signed transaction) into a value tree for inspection. */
+export async function decodeVecpak(packedBase58: string): Promise<VecpakTerm> {
+  return invoke<VecpakTerm>("vecpak_decode", {
+    packedBase58: packedBase58.trim(),
+  });
+}
+
+/**
+ * accepted: the node took it. already_submitted: an earlier attempt landed and the tx was found on chain.
+ * rejected: the node refused it. unknown: a retry was refused but an earlier attempt may still be
+ * pending; check the tx hash before sending anything else. failed: every retry hit a transient error
+ * and the tx is not on chain yet; the same signed tx can be resubmitted safely.
+ */
+export type SubmitOutcome = "accepted" | "already_submitted" | "rejected" | "unknown" | "failed";
+
 export type SubmitTransactionResult = {
   ok: boolean;
+  outcome: SubmitOutcome;
   txHash?: string;
   error?: string;
+  errorCode?: NetworkErrorCode;
   networkError?: boolean;
+  attempts: number;
 };
 
-export async function submitTransaction(signedTxJson: string): Promise<SubmitTransactionResult> {
-  type BackendResult = { ok: boolean; tx_hash?: string; error?: string; network_error: boolean };
-  const r = await invoke<BackendResult>("submit_transaction_to_network", {
-    apiUrl: getAmadeusApiUrl(),
-    signedTxJson,
-  });
+type SubmitBackendResult = {
+  ok: boolean;
+  outcome: SubmitOutcome;
+  tx_hash?: string;
+  error?: NetworkError | null;
+  network_error: boolean;
+  attempts: number;
+};
+
+function fromSubmitBackendResult(r: SubmitBackendResult): SubmitTransactionResult {
   return {
     ok: r.ok,
+    outcome: r.outcome,
     txHash: r.tx_hash,
-    error: r.error,
+    error: r.error?.message,
+    errorCode: r.error?.code,
     networkError: r.network_error,
+    attempts: r.attempts,
   };
 }
 
+/** Retries transient failures (with backoff) until the outcome is known. */
+export async function submitTransaction(signedTxJson: string): Promise<SubmitTransactionResult> {
+  const r = await invoke<SubmitBackendResult>("submit_transaction_to_network", {
+    apiUrl: getAmadeusApiUrl(),
+    signedTxJson,
+  });
+  return fromSubmitBackendResult(r);
+}
+
+/** Submits a signed transaction file written by signTransactionFile. */
+export async function broadcastTransactionFile(path: string): Promise<SubmitTransactionResult> {
+  const r = await invoke<SubmitBackendResult>("broadcast_transaction_file", {
+    apiUrl: getAmadeusApiUrl(),
+    path,
+  });
+  return fromSubmitBackendResult(r);
+}
+
 export type TransactionItem = {
   tx_hash?: string;
   kind: string;
   from_address?: string;
   to_address?: string;
   amount_flat: string;
+  /** amount_flat formatted as a decimal string, e.g. "1.5" */
+  amount?: string;
   block_height?: number;
   status?: string;
   memo?: string;
@@ -167,6 +822,7 @@ export type TransactionsResult = {
   next_sent_cursor?: string | null;
   next_received_cursor?: string | null;
   error?: string;
+  error_code?: NetworkErrorCode;
   network_error?: boolean;
 };
 
@@ -180,7 +836,7 @@ export async function getTransactions(
     transactions: TransactionItem[];
     next_sent_cursor?: string | null;
     next_received_cursor?: string | null;
-    error?: string;
+    error?: NetworkError | null;
     network_error: boolean;
   };
   const r = await invoke<BackendResult>("get_transactions", {
@@ -194,7 +850,8 @@ export async function getTransactions(
     transactions: r.transactions ?? [],
     next_sent_cursor: r.next_sent_cursor ?? null,
     next_received_cursor: r.next_received_cursor ?? null,
-    error: r.error,
+    error: r.error?.message,
+    error_code: r.error?.code,
     network_error: r.network_error,
   };
 }
@@ -203,6 +860,7 @@ export type TransactionStatusResult = {
   ok: boolean;
   status?: string | null;
   error?: string | null;
+  error_code?: NetworkErrorCode | null;
   network_error?: boolean;
 };
 
@@ -211,7 +869,7 @@ export async function getTransactionStatus(txHash: string): Promise<TransactionS
   type BackendResult = {
     ok: boolean;
     status?: string | null;
-    error?: string | null;
+    error?: NetworkError | null;
     network_error?: boolean;
   };
   const r = await invoke<BackendResult>("get_transaction_status", {
@@ -221,7 +879,8 @@ export async function getTransactionStatus(txHash: string): Promise<TransactionS
   return {
     ok: r.ok,
     status: r.status ?? null,
-    error: r.error ?? null,
+    error: r.error?.message ?? null,
+    error_code: r.error?.code ?? null,
     network_error: r.network_error ?? false,
   };
 }
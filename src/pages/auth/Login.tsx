@@ -25,7 +25,7 @@ import {
 // Lib
 import { loginSchema } from "@/lib/schemas";
 import { unlockWallet } from "@/lib/wallet";
-import { getErrorMessage } from "@/lib/utils";
+import { getErrorMessage, isCommandError } from "@/lib/utils";
 
 type LoginForm = z.infer<typeof loginSchema>;
 
@@ -37,6 +37,7 @@ export default function LoginPage() {
     const {
         handleSubmit,
         control,
+        setError,
         formState: { errors, isSubmitting },
     } = useForm<LoginForm>({
         resolver: zodResolver(loginSchema),
@@ -59,6 +60,10 @@ export default function LoginPage() {
             toast.success("Wallet unlocked");
             navigate("/", { state: { publicKeyBase58 }, replace: true });
         } catch (e) {
+            if (isCommandError(e) && e.code === "wrong_password") {
+                setError("password", { message: "Wrong password" });
+                return;
+            }
             const msg = getErrorMessage(e);
             toast.error("Failed to unlock wallet", { description: msg });
         }
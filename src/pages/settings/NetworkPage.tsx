@@ -5,7 +5,7 @@ import { toast } from "sonner";
 import { NetworkSection } from "@/components/settings/NetworkSection";
 
 // Lib
-import { getAmadeusApiUrl, setAmadeusApiUrl, checkNodeHealth } from "@/lib/wallet";
+import { getAmadeusApiUrl, setAmadeusApiUrl, checkNodeHealth, getNetworkSettingsError } from "@/lib/wallet";
 import { globals } from "@/lib/globals";
 import { copyToClipboard, getErrorMessage } from "@/lib/utils";
 
@@ -17,6 +17,16 @@ export default function NetworkPage() {
 
     useEffect(() => {
         setApiUrl(getAmadeusApiUrl());
+        getNetworkSettingsError()
+            .then((error) => {
+                if (error) {
+                    toast.error("Network settings are invalid; no requests are sent", {
+                        description: error.message,
+                        duration: Infinity,
+                    });
+                }
+            })
+            .catch(console.error);
     }, []);
 
     const handleSave = () => {
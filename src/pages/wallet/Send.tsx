@@ -39,6 +39,7 @@ export default function WalletSendPage() {
     const [recentAddresses, setRecentAddresses] = useState<string[]>(() => getRecentAddresses());
     const [addressValidating, setAddressValidating] = useState(false);
     const [addressValid, setAddressValid] = useState<boolean | null>(null);
+    const [addressError, setAddressError] = useState<string | null>(null);
     const [isSubmitting, setIsSubmitting] = useState(false);
 
     const {
@@ -76,11 +77,13 @@ export default function WalletSendPage() {
         setAddressValidating(true);
         setAddressValid(null);
         try {
-            const ok = await validateAddress(trimmed);
-            setAddressValid(ok);
-            return ok;
+            const result = await validateAddress(trimmed);
+            setAddressValid(result.valid);
+            setAddressError(result.message);
+            return result.valid;
         } catch {
             setAddressValid(false);
+            setAddressError(null);
             return false;
         } finally {
             setAddressValidating(false);
@@ -177,11 +180,17 @@ export default function WalletSendPage() {
                 setValue("password", "");
                 refetchBalance();
             } else {
-                const description = result.error
-                    ? result.networkError
-                        ? `Cannot reach the network: ${result.error}`
-                        : result.error
-                    : "The network is unavailable or rejected the transaction. Please try again later.";
+                const tx = result.txHash ? ` (Tx: ${result.txHash})` : "";
+                if (result.outcome === "unknown") {
+                    toast.warning("Transaction status unknown", {
+                        description: `A retry was refused, but an earlier attempt may still be pending${tx}. Check its status before sending again.`,
+                    });
+                    return;
+                }
+                const description =
+                    result.outcome === "failed"
+                        ? `No confirmation after ${result.attempts} attempts${result.error ? ` (${result.error})` : ""}. It may still be pending${tx}; check its status before trying again.`
+                        : result.error ?? "The network rejected the transaction.";
                 toast.error("Transaction could not be submitted", { description });
             }
         } catch (e) {
@@ -253,7 +262,7 @@ export default function WalletSendPage() {
                                             <p className="text-xs text-green-600 dark:text-green-400">Valid Amadeus address</p>
                                         )}
                                         {!addressValidating && addressValid === false && (
-                                            <p className="text-xs text-destructive">Invalid Amadeus address (must be Base58, 48 bytes)</p>
+                                            <p className="text-xs text-destructive">Invalid Amadeus address{addressError ? `: ${addressError}` : ""}</p>
                                         )}
                                     </>
                                 )}